@@ -1,110 +1,448 @@
 use anyhow::Result;
-use crossterm::event::{self, Event, KeyCode};
+use crossterm::event::{self, Event, KeyCode, KeyEvent};
 use ratatui::{Terminal, backend::Backend, widgets::ListState};
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::sync::mpsc::{Receiver, TryRecvError};
 use std::time::{Duration, Instant};
 
+pub mod alerts;
+pub mod config;
+pub mod diagnostics;
+pub mod docs;
+pub mod filter;
+pub mod format;
 pub mod model;
+pub mod recording;
 pub mod systemd;
 pub mod ui;
+pub mod watcher;
 
-use model::Service;
+use config::{Column, Config, NotifyRule, Preset, Scope, SortOrder};
+use diagnostics::Diagnostic;
+use docs::DocCommand;
+use filter::Filter;
+use model::{BootFailure, JournalUsage, LogChunk, Machine, Relation, Service, SymlinkChange};
+use recording::{Recorder, Replay, Step};
+use systemd::{LogRange, MachineAction, SystemdClient, UnitFileAction};
+
+// How often to re-read units when no D-Bus signals are available.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+// With signals we only resync occasionally, in case one was missed.
+const RESYNC_INTERVAL: Duration = Duration::from_secs(30);
+// Signals come in bursts (a restart alone is several), so refreshes they trigger are spaced out.
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+// How long each recorded key stays on screen during a replay.
+const REPLAY_STEP: Duration = Duration::from_millis(300);
+// How often the open log view checks for new lines.
+const LOG_REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+// How many log lines to fetch at first, and how many more each time older history is requested.
+const LOG_PAGE: usize = 100;
+// Most log lines kept while new ones come in; the oldest go first.
+const LOG_BUFFER: usize = 100_000;
 
 pub struct App {
+    client: Box<dyn SystemdClient>,
     services: Vec<Service>,
+    /// Units outside the service list that were jumped to, kept across refreshes until the
+    /// selection moves off them or the view is changed.
+    pinned_units: Vec<String>,
     list_state: ListState,
+    /// Units picked with Space for bulk enable/disable/mask.
+    marked: BTreeSet<String>,
     should_quit: bool,
     show_only_user_config: bool,
+    filter: Option<Filter>,
+    filter_query: String,
+    editing_filter: bool,
+    hide_inactive: bool,
+    sort_order: SortOrder,
+    presets: Vec<Preset>,
+    active_preset: Option<usize>,
+    columns: Vec<Column>,
+    notify_rules: Vec<NotifyRule>,
+    config_path: Option<PathBuf>,
+    diagnostic: Option<Diagnostic>,
+    /// Whether the unit list has loaded at least once, after which failures are only reported.
+    loaded: bool,
+    status_message: Option<String>,
+    journal_unavailable: bool,
 
     showing_logs: bool,
     logs: Vec<String>,
-    log_scroll: u16,
+    log_scroll: usize,
+    /// How many of a log file's newest lines are loaded; grows as older history is paged in.
+    log_lines: usize,
+    /// Where the journal was last read up to, so live updates only fetch what's new.
+    log_cursor: Option<String>,
+    /// The oldest loaded journal entry, found when older history is first paged in.
+    log_oldest: Option<String>,
+    last_log_refresh: Instant,
+    /// Set when the log view shows a machine's journal instead of the selected unit's.
+    log_machine: Option<String>,
+    /// The unit whose journal is open, so refreshing the list underneath can't switch it.
+    log_unit: Option<String>,
     stick_to_bottom: bool,
+    /// The journal usage popup over the log view.
+    journal_usage: Option<JournalUsage>,
+
+    showing_failures: bool,
+    boot_failures: Vec<BootFailure>,
+    failures_scroll: u16,
+
+    showing_columns: bool,
+    column_cursor: usize,
+
+    showing_dependencies: bool,
+    dependencies_of: String,
+    dependencies: Vec<Relation>,
+    dependency_cursor: usize,
+
+    showing_machines: bool,
+    machines: Vec<Machine>,
+    machine_cursor: usize,
+    last_machines_refresh: Instant,
+
+    showing_docs: bool,
+    doc_links: Vec<String>,
+    doc_cursor: usize,
+    /// A Documentation= URI to open once the current key has been handled.
+    pending_doc: Option<String>,
+
+    /// An enable/disable/mask waiting for confirmation, with the symlinks it will touch.
+    pending_unit_files: Option<UnitFileAction>,
+    pending_units: Vec<String>,
+    pending_changes: Vec<SymlinkChange>,
 
     last_data_tick: Instant,
     data_tick_rate: Duration,
+    unit_events: Option<Receiver<()>>,
+    /// A signal arrived that hasn't been acted on yet.
+    units_changed: bool,
+    recorder: Option<Recorder>,
 }
 
 impl App {
-    pub fn new() -> Self {
+    pub fn new(client: Box<dyn SystemdClient>, config: Config) -> Self {
         let mut list_state = ListState::default();
         list_state.select(Some(0));
 
         Self {
+            client,
             services: Vec::new(),
+            pinned_units: Vec::new(),
             list_state,
+            marked: BTreeSet::new(),
             should_quit: false,
             show_only_user_config: true,
+            filter: None,
+            filter_query: String::new(),
+            editing_filter: false,
+            hide_inactive: false,
+            sort_order: SortOrder::Name,
+            presets: config.presets,
+            active_preset: None,
+            columns: config.columns,
+            notify_rules: config.notify,
+            config_path: config.path,
+            diagnostic: None,
+            loaded: false,
+            status_message: None,
+            journal_unavailable: false,
 
             showing_logs: false,
             logs: Vec::new(),
             log_scroll: 0,
+            log_lines: LOG_PAGE,
+            log_cursor: None,
+            log_oldest: None,
+            last_log_refresh: Instant::now(),
+            log_machine: None,
+            log_unit: None,
             stick_to_bottom: true,
+            journal_usage: None,
+
+            showing_failures: false,
+            boot_failures: Vec::new(),
+            failures_scroll: 0,
+
+            showing_columns: false,
+            column_cursor: 0,
+
+            showing_dependencies: false,
+            dependencies_of: String::new(),
+            dependencies: Vec::new(),
+            dependency_cursor: 0,
+
+            showing_machines: false,
+            machines: Vec::new(),
+            machine_cursor: 0,
+            last_machines_refresh: Instant::now(),
+
+            showing_docs: false,
+            doc_links: Vec::new(),
+            doc_cursor: 0,
+            pending_doc: None,
+
+            pending_unit_files: None,
+            pending_units: Vec::new(),
+            pending_changes: Vec::new(),
 
             last_data_tick: Instant::now(),
-            data_tick_rate: Duration::from_secs(2),
+            data_tick_rate: POLL_INTERVAL,
+            unit_events: None,
+            units_changed: false,
+            recorder: None,
         }
     }
 
+    /// Writes every key to the recording alongside the calls the client records.
+    pub fn set_recorder(&mut self, recorder: Recorder) {
+        self.recorder = Some(recorder);
+    }
+
     fn get_current_view_services(&self) -> Vec<Service> {
-        if self.show_only_user_config {
-            self.services
-                .iter()
-                .filter(|s| s.is_user_config)
-                .cloned()
+        let mut services: Vec<Service> = self
+            .services
+            .iter()
+            .filter(|s| !self.show_only_user_config || s.is_user_config)
+            .filter(|s| !self.hide_inactive || !s.is_inactive())
+            .filter(|s| self.filter.as_ref().is_none_or(|f| f.matches(s)))
+            .cloned()
+            .collect();
+
+        // Services arrive sorted by name, so a stable sort keeps that as the tie-break.
+        if self.sort_order == SortOrder::State {
+            services.sort_by_key(|s| match s.active_state.as_str() {
+                "failed" => 0,
+                "active" => 1,
+                _ => 2,
+            });
+        }
+
+        services
+    }
+
+    /// Re-applies the search box, leaving the previous filter in place if it doesn't parse.
+    fn update_filter(&mut self) {
+        match Filter::parse(&self.filter_query) {
+            Ok(filter) => {
+                self.filter = filter;
+                self.view_changed();
+            }
+            Err(err) => self.status_message = Some(filter::describe_error(&err)),
+        }
+    }
+
+    /// Moves to the next preset, or back to the default view after the last one.
+    fn cycle_preset(&mut self) {
+        let next = match self.active_preset {
+            None if !self.presets.is_empty() => Some(0),
+            Some(i) if i + 1 < self.presets.len() => Some(i + 1),
+            _ => None,
+        };
+
+        match next.map(|i| &self.presets[i]) {
+            Some(preset) => {
+                self.filter_query = preset
+                    .filter
+                    .as_deref()
+                    .map(Filter::regex_query)
+                    .unwrap_or_default();
+                // Patterns were validated when the config was loaded.
+                self.filter = Filter::parse(&self.filter_query).ok().flatten();
+                self.sort_order = preset.sort;
+                self.show_only_user_config = preset.scope == Scope::Config;
+            }
+            None => {
+                self.filter = None;
+                self.filter_query.clear();
+                self.sort_order = SortOrder::Name;
+                self.show_only_user_config = true;
+            }
+        }
+
+        self.active_preset = next;
+        self.view_changed();
+    }
+
+    /// Starts over at the top after the scope, search, preset or hide-inactive setting changes.
+    fn view_changed(&mut self) {
+        self.list_state.select(Some(0));
+        self.pinned_units.clear();
+    }
+
+    /// Forgets jumped-to units other than the selected one; they drop out on the next refresh.
+    fn unpin_unselected(&mut self) {
+        let selected = self
+            .list_state
+            .selected()
+            .and_then(|index| self.get_current_view_services().get(index).cloned());
+        self.pinned_units
+            .retain(|name| selected.as_ref().is_some_and(|s| &s.name == name));
+    }
+
+    /// Selects a unit in the main list, loading it or clearing filters if it isn't visible.
+    fn jump_to_unit(&mut self, name: &str) {
+        let name = if self.services.iter().any(|s| s.name == name) {
+            name.to_string()
+        } else {
+            match self.client.get_unit(name) {
+                // Dependencies can name an alias, which comes back under the unit's own name.
+                Ok(unit) => {
+                    let name = unit.name.clone();
+                    if !self.services.iter().any(|s| s.name == name) {
+                        self.services.push(unit);
+                        self.services.sort_by(|a, b| a.name.cmp(&b.name));
+                        self.pinned_units.push(name.clone());
+                    }
+                    name
+                }
+                Err(err) => {
+                    self.status_message = Some(format!("{:#}", err));
+                    return;
+                }
+            }
+        };
+
+        if !self
+            .get_current_view_services()
+            .iter()
+            .any(|s| s.name == name)
+        {
+            let cleared: Vec<&str> = [
+                (self.active_preset.is_some(), "preset"),
+                (self.filter.is_some(), "search"),
+                (self.show_only_user_config, "config-only scope"),
+                (self.hide_inactive, "hide inactive"),
+            ]
+            .into_iter()
+            .filter_map(|(active, setting)| active.then_some(setting))
+            .collect();
+
+            self.show_only_user_config = false;
+            self.hide_inactive = false;
+            self.filter = None;
+            self.filter_query.clear();
+            self.active_preset = None;
+            self.status_message = Some(format!("Cleared {} to show {}", cleared.join(", "), name));
+        }
+
+        let index = self
+            .get_current_view_services()
+            .iter()
+            .position(|s| s.name == name);
+        self.list_state.select(index);
+    }
+
+    /// Previews an enable/disable/mask of the marked units, or the selected one if none are.
+    fn begin_unit_file_action(&mut self, action: UnitFileAction, services: &[Service]) {
+        let units: Vec<String> = if self.marked.is_empty() {
+            self.list_state
+                .selected()
+                .and_then(|index| services.get(index))
+                .map(|service| service.name.clone())
+                .into_iter()
                 .collect()
         } else {
-            self.services.clone()
+            self.marked.iter().cloned().collect()
+        };
+        if units.is_empty() {
+            return;
+        }
+
+        match self.client.preview_unit_files(&units, action) {
+            Ok(changes) => {
+                self.pending_unit_files = Some(action);
+                self.pending_units = units;
+                self.pending_changes = changes;
+            }
+            Err(err) => self.status_message = Some(format!("{:#}", err)),
+        }
+    }
+
+    /// Whether systemctl would refuse part of the previewed action.
+    fn unit_file_action_blocked(&self) -> bool {
+        self.pending_changes
+            .iter()
+            .any(|change| matches!(change, SymlinkChange::Blocked { .. }))
+    }
+
+    fn apply_unit_file_action(&mut self, action: UnitFileAction) {
+        let units = std::mem::take(&mut self.pending_units);
+        self.pending_unit_files = None;
+        self.pending_changes.clear();
+
+        match self.client.change_unit_files(&units, action) {
+            Ok(()) => {
+                self.status_message = Some(format!(
+                    "{} {} unit{}",
+                    action.past_tense(),
+                    units.len(),
+                    if units.len() == 1 { "" } else { "s" }
+                ));
+                self.marked.clear();
+            }
+            Err(err) => self.status_message = Some(format!("{:#}", err)),
+        }
+        self.force_next_refresh();
+    }
+
+    /// Shows or hides a column, keeping the display order fixed.
+    fn toggle_column(&mut self, column: Column) {
+        if self.columns.contains(&column) {
+            self.columns.retain(|&c| c != column);
+        } else {
+            self.columns = Column::ALL
+                .into_iter()
+                .filter(|&c| c == column || self.columns.contains(&c))
+                .collect();
+        }
+    }
+
+    /// Memory and uptime are only read when a column shows them or a notify rule watches them.
+    fn needs_runtime_properties(&self) -> bool {
+        self.columns
+            .iter()
+            .any(|column| matches!(column, Column::Memory | Column::Uptime))
+            || self
+                .notify_rules
+                .iter()
+                .any(|rule| rule.on_restart || rule.memory_max.is_some())
+    }
+
+    /// Closes the column menu, remembering the choice for next time.
+    fn close_column_chooser(&mut self) {
+        self.showing_columns = false;
+        // A newly shown memory or uptime column has nothing in it until the next refresh.
+        self.force_next_refresh();
+        if let Some(path) = &self.config_path {
+            if let Err(err) = config::save_columns(path, &self.columns) {
+                self.status_message = Some(format!("{:#}", err));
+            }
         }
     }
 
     pub fn run<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
-        self.refresh_services()?;
+        self.reload();
+        if self.diagnostic.is_none() {
+            self.start_watcher();
+        }
 
         let mut last_tick = Instant::now();
 
         let tick_rate = Duration::from_millis(100);
 
         loop {
-            let current_view_services = self.get_current_view_services();
             let terminal_size = terminal.size()?;
 
-            // We only fetch if NOT showing logs (to prevent UI jumps/lag while reading)
-            if !self.showing_logs && self.last_data_tick.elapsed() >= self.data_tick_rate {
-                self.refresh_services()?;
-                self.last_data_tick = Instant::now();
-            }
-
-            if self.showing_logs {
-                if let Some(index) = self.list_state.selected() {
-                    if let Some(service) = current_view_services.get(index) {
-                        // Ideally this should also be throttled, but for now we keep it
-                        // to ensure "live" logs feel live.
-                        if let Ok(new_logs) = systemd::get_service_logs(&service.name) {
-                            self.logs = new_logs;
-
-                            if self.stick_to_bottom {
-                                let popup_height =
-                                    (terminal_size.height * 80 / 100).saturating_sub(2);
-                                self.log_scroll =
-                                    (self.logs.len() as u16).saturating_sub(popup_height);
-                            }
-                        }
-                    }
-                }
+            self.refresh_if_due();
+
+            if self.last_log_refresh.elapsed() >= LOG_REFRESH_INTERVAL {
+                self.refresh_live_logs(terminal_size.height);
             }
 
-            terminal.draw(|f| {
-                ui::render(
-                    f,
-                    &current_view_services,
-                    &mut self.list_state,
-                    self.show_only_user_config,
-                    self.showing_logs,
-                    &self.logs,
-                    self.log_scroll,
-                    self.stick_to_bottom,
-                )
-            })?;
+            terminal.draw(|f| ui::render(f, self))?;
 
             let timeout = tick_rate
                 .checked_sub(last_tick.elapsed())
@@ -112,101 +450,706 @@ impl App {
 
             if crossterm::event::poll(timeout)? {
                 if let Event::Key(key) = event::read()? {
-                    if self.showing_logs {
-                        match key.code {
-                            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('l') => {
-                                self.showing_logs = false;
-                                self.logs.clear();
-                                self.log_scroll = 0;
-                                self.stick_to_bottom = true;
-
-                                self.force_next_refresh();
+                    self.on_key(key)?;
+                }
+            }
+
+            if let Some(uri) = self.pending_doc.take() {
+                self.open_documentation(&uri);
+                // man had the whole screen, so nothing on it can be trusted.
+                terminal.clear()?;
+            }
+
+            if last_tick.elapsed() >= tick_rate {
+                last_tick = Instant::now();
+            }
+
+            if self.should_quit {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Handles a key typed by the user, adding it to the recording if there is one.
+    fn on_key(&mut self, key: KeyEvent) -> Result<()> {
+        if let Some(recorder) = &self.recorder {
+            recorder.key(key.code);
+        }
+        self.handle_key(key)
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Result<()> {
+        let current_view_services = self.get_current_view_services();
+
+        // Messages only describe the most recent action.
+        self.status_message = None;
+
+        if self.diagnostic.is_some() {
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => self.should_quit = true,
+                KeyCode::Char('r') => {
+                    self.reload();
+                    if self.diagnostic.is_none() && self.unit_events.is_none() {
+                        self.start_watcher();
+                    }
+                }
+                _ => {}
+            }
+        } else if self.editing_filter {
+            match key.code {
+                KeyCode::Enter => self.editing_filter = false,
+                KeyCode::Esc => {
+                    self.editing_filter = false;
+                    self.filter_query.clear();
+                    self.update_filter();
+                }
+                KeyCode::Backspace => {
+                    self.filter_query.pop();
+                    self.update_filter();
+                }
+                KeyCode::Char(c) => {
+                    self.filter_query.push(c);
+                    self.update_filter();
+                }
+                _ => {}
+            }
+        } else if self.journal_usage.is_some() {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('V') => {
+                    self.journal_usage = None;
+                }
+                _ => {}
+            }
+        } else if self.showing_logs {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('l') => {
+                    self.showing_logs = false;
+                    self.logs.clear();
+                    self.log_scroll = 0;
+                    self.log_lines = LOG_PAGE;
+                    self.log_cursor = None;
+                    self.log_oldest = None;
+                    self.log_machine = None;
+                    self.log_unit = None;
+                    self.stick_to_bottom = true;
+
+                    self.force_next_refresh();
+                }
+                KeyCode::Char('j') | KeyCode::Down => {
+                    self.stick_to_bottom = false;
+                    if self.log_scroll < self.logs.len().saturating_sub(1) {
+                        self.log_scroll += 1;
+                    }
+                }
+                KeyCode::Char('k') | KeyCode::Up => {
+                    self.stick_to_bottom = false;
+                    if self.log_scroll > 0 {
+                        self.log_scroll -= 1;
+                    } else {
+                        self.load_older_logs();
+                    }
+                }
+
+                KeyCode::Char('G') | KeyCode::End => {
+                    self.stick_to_bottom = true;
+                }
+                // Journal usage is per unit, which a machine's journal isn't.
+                KeyCode::Char('V') if self.log_machine.is_none() => {
+                    if let Some(unit) = &self.log_unit {
+                        match self.client.get_journal_usage(unit) {
+                            Ok(usage) => self.journal_usage = Some(usage),
+                            Err(err) => self.status_message = Some(format!("{:#}", err)),
+                        }
+                    }
+                }
+                _ => {}
+            }
+        } else if self.showing_machines {
+            let selected = self.machines.get(self.machine_cursor).cloned();
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('m') => {
+                    self.showing_machines = false;
+                }
+                KeyCode::Char('j') | KeyCode::Down => {
+                    self.machine_cursor =
+                        (self.machine_cursor + 1).min(self.machines.len().saturating_sub(1));
+                }
+                KeyCode::Char('k') | KeyCode::Up => {
+                    self.machine_cursor = self.machine_cursor.saturating_sub(1);
+                }
+                KeyCode::Char('s') | KeyCode::Char('x') | KeyCode::Char('t') => {
+                    let action = match key.code {
+                        KeyCode::Char('s') => MachineAction::Start,
+                        KeyCode::Char('x') => MachineAction::PowerOff,
+                        _ => MachineAction::Terminate,
+                    };
+                    if let Some(machine) = selected {
+                        if let Err(err) = self.client.control_machine(&machine.name, action) {
+                            self.status_message = Some(format!("{:#}", err));
+                        }
+                        self.refresh_machines();
+                    }
+                }
+                KeyCode::Char('l') => {
+                    if let Some(machine) = selected {
+                        let range = LogRange::Last(LOG_PAGE);
+                        match self.client.get_machine_logs(&machine.name, &range) {
+                            Ok(chunk) => {
+                                self.open_logs(chunk);
+                                self.log_machine = Some(machine.name);
                             }
-                            KeyCode::Char('j') | KeyCode::Down => {
-                                self.stick_to_bottom = false;
-                                if self.log_scroll < (self.logs.len() as u16).saturating_sub(1) {
-                                    self.log_scroll += 1;
+                            Err(err) => self.status_message = Some(format!("{:#}", err)),
+                        }
+                    }
+                }
+                _ => {}
+            }
+        } else if self.showing_failures {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('b') => {
+                    self.showing_failures = false;
+                    self.boot_failures.clear();
+                    self.failures_scroll = 0;
+                }
+                KeyCode::Char('j') | KeyCode::Down => {
+                    // Each failure takes two lines.
+                    let max_scroll = (self.boot_failures.len() as u16 * 2).saturating_sub(1);
+                    if self.failures_scroll < max_scroll {
+                        self.failures_scroll += 1;
+                    }
+                }
+                KeyCode::Char('k') | KeyCode::Up => {
+                    self.failures_scroll = self.failures_scroll.saturating_sub(1);
+                }
+                _ => {}
+            }
+        } else if let Some(action) = self.pending_unit_files {
+            match key.code {
+                KeyCode::Char('y') | KeyCode::Enter if !self.unit_file_action_blocked() => {
+                    self.apply_unit_file_action(action)
+                }
+                KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('q') => {
+                    self.pending_unit_files = None;
+                    self.pending_units.clear();
+                    self.pending_changes.clear();
+                }
+                _ => {}
+            }
+        } else if self.showing_dependencies {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('d') => {
+                    self.showing_dependencies = false;
+                }
+                KeyCode::Char('j') | KeyCode::Down => {
+                    self.dependency_cursor =
+                        (self.dependency_cursor + 1).min(self.dependencies.len().saturating_sub(1));
+                }
+                KeyCode::Char('k') | KeyCode::Up => {
+                    self.dependency_cursor = self.dependency_cursor.saturating_sub(1);
+                }
+                KeyCode::Enter => {
+                    if let Some(relation) = self.dependencies.get(self.dependency_cursor) {
+                        let unit = relation.unit.clone();
+                        self.showing_dependencies = false;
+                        self.jump_to_unit(&unit);
+                    }
+                }
+                _ => {}
+            }
+        } else if self.showing_docs {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('o') => self.showing_docs = false,
+                KeyCode::Char('j') | KeyCode::Down => {
+                    self.doc_cursor =
+                        (self.doc_cursor + 1).min(self.doc_links.len().saturating_sub(1));
+                }
+                KeyCode::Char('k') | KeyCode::Up => {
+                    self.doc_cursor = self.doc_cursor.saturating_sub(1);
+                }
+                KeyCode::Enter => {
+                    self.pending_doc = self.doc_links.get(self.doc_cursor).cloned();
+                    self.showing_docs = false;
+                }
+                _ => {}
+            }
+        } else if self.showing_columns {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('c') => {
+                    self.close_column_chooser()
+                }
+                KeyCode::Char('j') | KeyCode::Down => {
+                    self.column_cursor = (self.column_cursor + 1) % Column::ALL.len();
+                }
+                KeyCode::Char('k') | KeyCode::Up => {
+                    self.column_cursor =
+                        (self.column_cursor + Column::ALL.len() - 1) % Column::ALL.len();
+                }
+                KeyCode::Char(' ') | KeyCode::Enter => {
+                    self.toggle_column(Column::ALL[self.column_cursor])
+                }
+                _ => {}
+            }
+        } else {
+            match key.code {
+                KeyCode::Char('q') => self.should_quit = true,
+
+                KeyCode::Char('j') => {
+                    self.next(&current_view_services);
+                    self.unpin_unselected();
+                }
+                KeyCode::Char('k') => {
+                    self.previous(&current_view_services);
+                    self.unpin_unselected();
+                }
+
+                KeyCode::Char(' ') => {
+                    if let Some(service) = self
+                        .list_state
+                        .selected()
+                        .and_then(|index| current_view_services.get(index))
+                    {
+                        if !self.marked.remove(&service.name) {
+                            self.marked.insert(service.name.clone());
+                        }
+                        self.next(&current_view_services);
+                    }
+                }
+                KeyCode::Esc => self.marked.clear(),
+                KeyCode::Char('E') => {
+                    self.begin_unit_file_action(UnitFileAction::Enable, &current_view_services)
+                }
+                KeyCode::Char('D') => {
+                    self.begin_unit_file_action(UnitFileAction::Disable, &current_view_services)
+                }
+                KeyCode::Char('M') => {
+                    self.begin_unit_file_action(UnitFileAction::Mask, &current_view_services)
+                }
+
+                KeyCode::Tab => {
+                    self.show_only_user_config = !self.show_only_user_config;
+                    self.view_changed();
+                }
+
+                KeyCode::Char('p') => self.cycle_preset(),
+                KeyCode::Char('/') => self.editing_filter = true,
+                KeyCode::Char('h') => {
+                    self.hide_inactive = !self.hide_inactive;
+                    self.view_changed();
+                }
+                KeyCode::Char('c') => {
+                    self.showing_columns = true;
+                    self.column_cursor = 0;
+                }
+
+                KeyCode::Char('l') => {
+                    if let Some(index) = self.list_state.selected() {
+                        if let Some(service) = current_view_services.get(index) {
+                            let range = LogRange::Last(LOG_PAGE);
+                            match self.client.get_service_logs(&service.name, &range) {
+                                Ok(chunk) => {
+                                    self.open_logs(chunk);
+                                    self.log_unit = Some(service.name.clone());
                                 }
-                            }
-                            KeyCode::Char('k') | KeyCode::Up => {
-                                self.stick_to_bottom = false;
-                                if self.log_scroll > 0 {
-                                    self.log_scroll -= 1;
+                                Err(err) => {
+                                    if err.downcast_ref::<systemd::JournalUnavailable>().is_some() {
+                                        self.journal_unavailable = true;
+                                    }
+                                    self.status_message = Some(format!("{:#}", err));
                                 }
                             }
+                        }
+                    }
+                }
 
-                            KeyCode::Char('G') | KeyCode::End => {
-                                self.stick_to_bottom = true;
+                KeyCode::Char('d') => {
+                    if let Some(service) = self
+                        .list_state
+                        .selected()
+                        .and_then(|index| current_view_services.get(index))
+                    {
+                        match self.client.get_dependencies(&service.name) {
+                            Ok(dependencies) => {
+                                self.dependencies_of = service.name.clone();
+                                self.dependencies = dependencies;
+                                self.dependency_cursor = 0;
+                                self.showing_dependencies = true;
                             }
-                            _ => {}
+                            Err(err) => self.status_message = Some(format!("{:#}", err)),
                         }
-                    } else {
-                        match key.code {
-                            KeyCode::Char('q') => self.should_quit = true,
+                    }
+                }
 
-                            KeyCode::Char('j') => self.next(&current_view_services),
-                            KeyCode::Char('k') => self.previous(&current_view_services),
+                KeyCode::Char('m') => {
+                    self.showing_machines = true;
+                    self.machine_cursor = 0;
+                    self.refresh_machines();
+                }
 
-                            KeyCode::Tab => {
-                                self.show_only_user_config = !self.show_only_user_config;
-                                self.list_state.select(Some(0));
+                KeyCode::Char('o') => {
+                    if let Some(service) = self
+                        .list_state
+                        .selected()
+                        .and_then(|index| current_view_services.get(index))
+                    {
+                        match self.client.get_documentation(&service.name) {
+                            Ok(links) if links.is_empty() => {
+                                self.status_message =
+                                    Some(format!("{} has no Documentation=", service.name));
                             }
-
-                            KeyCode::Char('l') => {
-                                if let Some(index) = self.list_state.selected() {
-                                    if let Some(service) = current_view_services.get(index) {
-                                        match systemd::get_service_logs(&service.name) {
-                                            Ok(logs) => {
-                                                self.logs = logs;
-                                                self.showing_logs = true;
-                                                self.log_scroll = 0;
-                                                self.stick_to_bottom = true;
-                                            }
-                                            Err(_) => {
-                                                // Handle error
-                                            }
-                                        }
-                                    }
-                                }
+                            // With a single link there's nothing to choose.
+                            Ok(links) if links.len() == 1 => {
+                                self.pending_doc = links.into_iter().next();
                             }
-
-                            KeyCode::Char('s') => self.perform_action(
-                                systemd::ServiceAction::Start,
-                                &current_view_services,
-                            )?,
-                            KeyCode::Char('x') => self.perform_action(
-                                systemd::ServiceAction::Stop,
-                                &current_view_services,
-                            )?,
-                            KeyCode::Char('r') => self.perform_action(
-                                systemd::ServiceAction::Restart,
-                                &current_view_services,
-                            )?,
-                            _ => {}
+                            Ok(links) => {
+                                self.doc_links = links;
+                                self.doc_cursor = 0;
+                                self.showing_docs = true;
+                            }
+                            Err(err) => self.status_message = Some(format!("{:#}", err)),
                         }
                     }
                 }
+
+                KeyCode::Char('b') => match self.client.get_boot_failures() {
+                    Ok(failures) => {
+                        self.boot_failures = failures;
+                        self.showing_failures = true;
+                        self.failures_scroll = 0;
+                    }
+                    Err(err) => self.status_message = Some(format!("{:#}", err)),
+                },
+
+                KeyCode::Char('s') => {
+                    self.perform_action(systemd::ServiceAction::Start, &current_view_services)?
+                }
+                KeyCode::Char('x') => {
+                    self.perform_action(systemd::ServiceAction::Stop, &current_view_services)?
+                }
+                KeyCode::Char('r') => {
+                    self.perform_action(systemd::ServiceAction::Restart, &current_view_services)?
+                }
+                _ => {}
             }
+        }
 
-            if last_tick.elapsed() >= tick_rate {
-                last_tick = Instant::now();
+        Ok(())
+    }
+
+    /// Shows the first page of a log, following its end.
+    fn open_logs(&mut self, chunk: LogChunk) {
+        self.logs = chunk.lines;
+        self.log_cursor = chunk.cursor;
+        self.log_oldest = None;
+        self.showing_logs = true;
+        self.log_scroll = 0;
+        self.log_lines = LOG_PAGE;
+        self.stick_to_bottom = true;
+        self.last_log_refresh = Instant::now();
+    }
+
+    /// Fetches another page of history above the top of the log view, keeping the view in place.
+    fn load_older_logs(&mut self) {
+        let Some(result) = self.fetch_older_logs() else {
+            return;
+        };
+
+        match result {
+            Ok(lines) => {
+                if lines.is_empty() {
+                    self.status_message = Some("Start of log".to_string());
+                    return;
+                }
+                let added = lines.len();
+                self.logs.splice(..0, lines);
+                // Land on the line just above the previous top.
+                self.log_scroll = added - 1;
             }
+            Err(err) => self.status_message = Some(format!("{:#}", err)),
+        }
+    }
 
-            if self.should_quit {
+    /// Reads the page of lines above the oldest one loaded. A journal is read back from the
+    /// oldest entry's cursor; a log file has none, so its tail is re-read a page longer.
+    fn fetch_older_logs(&mut self) -> Option<Result<Vec<String>>> {
+        let Some(newest) = self.log_cursor.clone() else {
+            let lines = self.log_lines + LOG_PAGE;
+            return Some(self.fetch_logs(&LogRange::Last(lines))?.map(|chunk| {
+                let added = chunk.lines.len().saturating_sub(self.logs.len());
+                self.log_lines = lines;
+                chunk.lines[..added].to_vec()
+            }));
+        };
+
+        let oldest = match self.log_oldest.clone() {
+            Some(oldest) => oldest,
+            // Found once by walking back over what's loaded, from the newest entry.
+            None => {
+                let loaded = systemd::count_log_entries(&self.logs).saturating_sub(1);
+                match self.fetch_logs(&LogRange::Before(newest.clone(), loaded))? {
+                    Ok(chunk) => chunk.cursor.unwrap_or(newest),
+                    Err(err) => return Some(Err(err)),
+                }
+            }
+        };
+        let result = self.fetch_logs(&LogRange::Before(oldest.clone(), LOG_PAGE))?;
+        Some(result.map(|chunk| {
+            self.log_oldest = Some(chunk.cursor.unwrap_or(oldest));
+            chunk.lines
+        }))
+    }
+
+    /// Drives the TUI from a recording instead of the keyboard; any real key stops it early.
+    pub fn replay<B: Backend>(
+        &mut self,
+        terminal: &mut Terminal<B>,
+        replay: &Replay,
+    ) -> Result<()> {
+        loop {
+            terminal.draw(|f| ui::render(f, self))?;
+
+            // Pause on keys so the viewer can follow; background refreshes just happen.
+            if replay.next_is_key() && crossterm::event::poll(REPLAY_STEP)? {
+                if let Event::Key(_) = event::read()? {
+                    return Ok(());
+                }
+            }
+
+            if !self.replay_step(replay, terminal.size()?.height)? || self.should_quit {
+                break;
+            }
+        }
+
+        if self.should_quit {
+            return Ok(());
+        }
+        self.status_message = Some("Replay finished, press any key to quit".to_string());
+        terminal.draw(|f| ui::render(f, self))?;
+        loop {
+            if let Event::Key(_) = event::read()? {
                 return Ok(());
             }
         }
     }
 
+    /// Applies the next recorded key or background call; false once the recording is used up.
+    fn replay_step(&mut self, replay: &Replay, height: u16) -> Result<bool> {
+        let remaining = replay.remaining();
+
+        match replay.next_step() {
+            Some(Step::Key(key)) => {
+                self.handle_key(key)?;
+                // The replay backend doesn't open anything, so this only clears the request.
+                if let Some(uri) = self.pending_doc.take() {
+                    self.open_documentation(&uri);
+                }
+            }
+            // Calls made outside of key handling were timer driven in the recorded session.
+            Some(Step::Call(method)) => {
+                match method.as_str() {
+                    "get_user_services" => self.reload(),
+                    "get_service_logs" | "get_machine_logs" => self.refresh_live_logs(height),
+                    "get_machines" => self.refresh_machines(),
+                    _ => {}
+                }
+                // If the app didn't make that call this time, move past it rather than stall.
+                if replay.remaining() == remaining {
+                    replay.skip();
+                }
+            }
+            None => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Adds new lines to the open log view, following the end if auto-scroll is on.
+    fn refresh_live_logs(&mut self, terminal_height: u16) {
+        if !self.showing_logs {
+            return;
+        }
+        self.last_log_refresh = Instant::now();
+
+        let range = match &self.log_cursor {
+            Some(cursor) => LogRange::After(cursor.clone()),
+            // A log file has no cursor, so its tail is re-read, but only while it's followed.
+            None if self.stick_to_bottom => LogRange::Last(self.log_lines),
+            None => return,
+        };
+        if let Some(Ok(chunk)) = self.fetch_logs(&range) {
+            match range {
+                LogRange::After(_) => {
+                    self.logs.extend(chunk.lines);
+                    self.trim_logs();
+                }
+                _ => self.logs = chunk.lines,
+            }
+            if chunk.cursor.is_some() {
+                self.log_cursor = chunk.cursor;
+            }
+
+            if self.stick_to_bottom {
+                let popup_height = (terminal_height as usize * 80 / 100).saturating_sub(2);
+                self.log_scroll = self.logs.len().saturating_sub(popup_height);
+            }
+        }
+    }
+
+    /// Drops the oldest lines past LOG_BUFFER, so following a chatty unit can't grow it forever.
+    fn trim_logs(&mut self) {
+        let Some(mut excess) = self.logs.len().checked_sub(LOG_BUFFER) else {
+            return;
+        };
+        if excess == 0 {
+            return;
+        }
+        // Only whole entries go, not the first lines of a multi-line message.
+        while self
+            .logs
+            .get(excess)
+            .is_some_and(|line| line.starts_with(char::is_whitespace))
+        {
+            excess += 1;
+        }
+        self.logs.drain(..excess);
+        self.log_scroll = self.log_scroll.saturating_sub(excess);
+        // The oldest entry was dropped, so it's walked back to again if older history is wanted.
+        self.log_oldest = None;
+    }
+
+    /// Reads the log being viewed: a machine's journal, or a unit's.
+    fn fetch_logs(&self, range: &LogRange) -> Option<Result<LogChunk>> {
+        if let Some(machine) = &self.log_machine {
+            return Some(self.client.get_machine_logs(machine, range));
+        }
+        let unit = self.log_unit.as_ref()?;
+        Some(self.client.get_service_logs(unit, range))
+    }
+
+    fn refresh_machines(&mut self) {
+        self.last_machines_refresh = Instant::now();
+        match self.client.get_machines() {
+            Ok(machines) => {
+                self.machines = machines;
+                self.machine_cursor = self
+                    .machine_cursor
+                    .min(self.machines.len().saturating_sub(1));
+            }
+            Err(err) => self.status_message = Some(format!("{:#}", err)),
+        }
+    }
+
+    fn open_documentation(&mut self, uri: &str) {
+        if !self.client.should_open_documentation(uri) {
+            return;
+        }
+        let result = match docs::command_for(uri) {
+            Some(DocCommand::Man(args)) => docs::run_man(&args),
+            Some(DocCommand::Url(url)) => docs::open_url(&url),
+            None => Err(anyhow::anyhow!("Don't know how to open {}", uri)),
+        };
+        if let Err(err) = result {
+            self.status_message = Some(format!("{:#}", err));
+        }
+    }
+
+    /// Runs the background refreshes that are due on this iteration.
+    fn refresh_if_due(&mut self) {
+        // This keeps going under the log view, which follows its own unit, since alerts are
+        // raised from it. Retrying after a failure is left up to the user.
+        if self.diagnostic.is_none() && self.data_is_stale() {
+            self.reload();
+        }
+        // Unit signals say nothing about machines, so these follow the poll even while the
+        // unit list only resyncs now and then.
+        if self.showing_machines
+            && !self.showing_logs
+            && self.last_machines_refresh.elapsed() >= POLL_INTERVAL
+        {
+            self.refresh_machines();
+        }
+    }
+
+    /// Whether the unit list should be re-read on this iteration.
+    fn data_is_stale(&mut self) -> bool {
+        let mut changed = false;
+
+        if let Some(events) = &self.unit_events {
+            loop {
+                match events.try_recv() {
+                    Ok(()) => self.units_changed = true,
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        // The watcher died (e.g. the bus went away), fall back to polling.
+                        self.unit_events = None;
+                        self.data_tick_rate = POLL_INTERVAL;
+                        changed = true;
+                        break;
+                    }
+                }
+            }
+        }
+
+        let elapsed = self.last_data_tick.elapsed();
+        changed
+            || (self.units_changed && elapsed >= MIN_REFRESH_INTERVAL)
+            || elapsed >= self.data_tick_rate
+    }
+
     fn force_next_refresh(&mut self) {
-        // We set the last_tick to the past, ensuring elapsed() > 2s
+        // We set the last_tick to the past, ensuring elapsed() > data_tick_rate
         self.last_data_tick = Instant::now()
             .checked_sub(self.data_tick_rate * 2)
             .unwrap_or(Instant::now());
     }
 
+    fn start_watcher(&mut self) {
+        self.unit_events = self.client.watch_units();
+        if self.unit_events.is_some() {
+            self.data_tick_rate = RESYNC_INTERVAL;
+        }
+    }
+
+    /// Re-reads the unit list. If systemd can't be reached on the first load this switches to
+    /// the diagnostic screen; later failures keep the last list and try again next time.
+    fn reload(&mut self) {
+        match self.refresh_services() {
+            Ok(()) => {
+                self.diagnostic = None;
+                self.loaded = true;
+            }
+            Err(err) if self.loaded => self.status_message = Some(format!("{:#}", err)),
+            Err(err) => self.diagnostic = Some(diagnostics::diagnose(&err)),
+        }
+        self.last_data_tick = Instant::now();
+        self.units_changed = false;
+    }
+
     fn refresh_services(&mut self) -> Result<()> {
-        let new_services = systemd::get_user_services()?;
+        let mut new_services = self
+            .client
+            .get_user_services(self.needs_runtime_properties())?;
+
+        let missing: Vec<&String> = self
+            .pinned_units
+            .iter()
+            .filter(|name| !new_services.iter().any(|s| &s.name == *name))
+            .collect();
+        if !missing.is_empty() {
+            // A pinned unit that can no longer be looked up just drops out of the list.
+            new_services.extend(
+                missing
+                    .iter()
+                    .filter_map(|name| self.client.get_unit(name).ok()),
+            );
+            new_services.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+
+        let alerts = alerts::detect(&self.notify_rules, &self.services, &new_services);
+        for alert in &alerts {
+            self.client.notify("systemd-tui-monitor", alert);
+        }
+        if !alerts.is_empty() {
+            self.status_message = Some(alerts.join(" | "));
+        }
 
         self.services = new_services;
 
@@ -266,7 +1209,7 @@ impl App {
         if let Some(index) = self.list_state.selected() {
             if let Some(service) = services.get(index) {
                 // In a production app, we would spawn a thread here.
-                let _ = systemd::control_service(&service.name, action);
+                let _ = self.client.control_service(&service.name, action);
 
                 // we force the next loop iteration to refresh data.
                 self.force_next_refresh();
@@ -275,3 +1218,846 @@ impl App {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use systemd::ServiceAction;
+    use systemd::mock::{MockClient, service};
+
+    fn sample_services() -> Vec<Service> {
+        vec![
+            service("alpha.service", "active", "running", true),
+            service("beta.service", "inactive", "dead", false),
+            service("gamma.service", "failed", "failed", true),
+        ]
+    }
+
+    fn app_with(client: &MockClient) -> App {
+        let mut app = App::new(Box::new(client.clone()), Config::default());
+        app.refresh_services().unwrap();
+        app
+    }
+
+    fn press(app: &mut App, code: KeyCode) {
+        app.handle_key(KeyEvent::from(code)).unwrap();
+    }
+
+    fn selected_name(app: &App) -> Option<String> {
+        let index = app.list_state.selected()?;
+        app.get_current_view_services()
+            .get(index)
+            .map(|s| s.name.clone())
+    }
+
+    #[test]
+    fn user_config_view_hides_other_services() {
+        let client = MockClient::new(sample_services());
+        let mut app = app_with(&client);
+
+        let names: Vec<_> = app
+            .get_current_view_services()
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(names, ["alpha.service", "gamma.service"]);
+
+        press(&mut app, KeyCode::Tab);
+        assert_eq!(app.get_current_view_services().len(), 3);
+        assert_eq!(app.list_state.selected(), Some(0));
+    }
+
+    #[test]
+    fn selection_wraps_around() {
+        let client = MockClient::new(sample_services());
+        let mut app = app_with(&client);
+
+        press(&mut app, KeyCode::Char('k'));
+        assert_eq!(selected_name(&app).as_deref(), Some("gamma.service"));
+
+        press(&mut app, KeyCode::Char('j'));
+        assert_eq!(selected_name(&app).as_deref(), Some("alpha.service"));
+    }
+
+    #[test]
+    fn refresh_clamps_selection_when_list_shrinks() {
+        let client = MockClient::new(sample_services());
+        let mut app = app_with(&client);
+        press(&mut app, KeyCode::Char('j'));
+
+        client.set_services(vec![service("alpha.service", "active", "running", true)]);
+        app.refresh_services().unwrap();
+        assert_eq!(app.list_state.selected(), Some(0));
+
+        client.set_services(Vec::new());
+        app.refresh_services().unwrap();
+        assert_eq!(app.list_state.selected(), None);
+    }
+
+    #[test]
+    fn actions_target_the_selected_service() {
+        let client = MockClient::new(sample_services());
+        let mut app = app_with(&client);
+
+        press(&mut app, KeyCode::Char('s'));
+        press(&mut app, KeyCode::Char('j'));
+        press(&mut app, KeyCode::Char('x'));
+        press(&mut app, KeyCode::Char('r'));
+
+        assert_eq!(
+            client.actions(),
+            [
+                ("alpha.service".to_string(), ServiceAction::Start),
+                ("gamma.service".to_string(), ServiceAction::Stop),
+                ("gamma.service".to_string(), ServiceAction::Restart),
+            ]
+        );
+    }
+
+    #[test]
+    fn bursts_of_unit_signals_refresh_once() {
+        let client = MockClient::new(sample_services());
+        let mut app = app_with(&client);
+        let (tx, rx) = std::sync::mpsc::channel();
+        app.unit_events = Some(rx);
+        app.data_tick_rate = RESYNC_INTERVAL;
+
+        app.reload();
+        for _ in 0..5 {
+            tx.send(()).unwrap();
+        }
+        assert!(!app.data_is_stale());
+
+        // The burst is remembered until enough time has passed.
+        app.last_data_tick = Instant::now() - MIN_REFRESH_INTERVAL;
+        assert!(app.data_is_stale());
+        app.reload();
+        assert!(!app.data_is_stale());
+    }
+
+    #[test]
+    fn machines_refresh_on_the_poll_while_units_follow_signals() {
+        let client = MockClient::new(sample_services());
+        let mut app = app_with(&client);
+        let (_tx, rx) = std::sync::mpsc::channel();
+        app.unit_events = Some(rx);
+        app.data_tick_rate = RESYNC_INTERVAL;
+        app.reload();
+
+        press(&mut app, KeyCode::Char('m'));
+        assert!(app.machines.is_empty());
+        client.set_machines(vec![Machine {
+            name: "fedora-dev".to_string(),
+            class: "container".to_string(),
+            os: "fedora".to_string(),
+            running: true,
+        }]);
+
+        app.refresh_if_due();
+        assert!(app.machines.is_empty());
+
+        app.last_machines_refresh = Instant::now() - POLL_INTERVAL;
+        app.refresh_if_due();
+        assert_eq!(app.machines.len(), 1);
+        // The unit list itself waits for a signal or the resync.
+        assert!(!app.data_is_stale());
+    }
+
+    #[test]
+    fn alerts_still_go_out_while_logs_are_open() {
+        let client = MockClient::new(sample_services());
+        let config = Config {
+            notify: vec![NotifyRule {
+                unit: "*".to_string(),
+                on_failure: true,
+                on_restart: false,
+                memory_max: None,
+            }],
+            ..Config::default()
+        };
+        let mut app = App::new(Box::new(client.clone()), config);
+        app.reload();
+        press(&mut app, KeyCode::Char('l'));
+        assert_eq!(app.log_unit.as_deref(), Some("alpha.service"));
+
+        client.set_services(vec![
+            service("alpha.service", "failed", "failed", true),
+            service("beta.service", "inactive", "dead", false),
+            service("gamma.service", "active", "running", true),
+        ]);
+        app.force_next_refresh();
+        app.refresh_if_due();
+        assert_eq!(client.notifications(), ["alpha.service failed"]);
+        // The log view stays on its unit whatever the list does.
+        assert!(app.showing_logs);
+        assert_eq!(app.log_unit.as_deref(), Some("alpha.service"));
+    }
+
+    #[test]
+    fn log_view_opens_and_closes() {
+        let client = MockClient::new(sample_services());
+        client.set_logs(vec!["started".to_string(), "stopped".to_string()]);
+        let mut app = app_with(&client);
+
+        press(&mut app, KeyCode::Char('l'));
+        assert!(app.showing_logs);
+        assert_eq!(app.logs.len(), 2);
+
+        // Navigation keys scroll the logs rather than moving the selection.
+        press(&mut app, KeyCode::Char('j'));
+        assert_eq!(app.log_scroll, 1);
+        assert!(!app.stick_to_bottom);
+        assert_eq!(selected_name(&app).as_deref(), Some("alpha.service"));
+
+        press(&mut app, KeyCode::Esc);
+        assert!(!app.showing_logs);
+        assert!(app.logs.is_empty());
+    }
+
+    #[test]
+    fn refresh_pipeline_from_fixtures() {
+        let client = MockClient::from_fixtures(
+            include_str!("../tests/fixtures/list-units.txt"),
+            include_str!("../tests/fixtures/list-unit-files.txt"),
+            &["syncthing.service", "obex.service"],
+        );
+        let mut app = app_with(&client);
+
+        let names: Vec<_> = app
+            .get_current_view_services()
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(names, ["obex.service", "syncthing.service"]);
+
+        press(&mut app, KeyCode::Char('j'));
+        press(&mut app, KeyCode::Char('r'));
+        assert_eq!(
+            client.actions(),
+            [("syncthing.service".to_string(), ServiceAction::Restart)]
+        );
+    }
+
+    #[test]
+    fn unreachable_bus_shows_diagnostic_until_retry_succeeds() {
+        let client = MockClient::new(sample_services());
+        client.set_services_error(Some("Failed to connect to bus: No medium found"));
+        let mut app = App::new(Box::new(client.clone()), Config::default());
+
+        app.reload();
+        assert!(app.diagnostic.is_some());
+
+        // Normal keys are ignored while the diagnostic is up.
+        press(&mut app, KeyCode::Char('s'));
+        assert!(client.actions().is_empty());
+
+        client.set_services_error(None);
+        press(&mut app, KeyCode::Char('r'));
+        assert!(app.diagnostic.is_none());
+        assert_eq!(app.services.len(), 3);
+    }
+
+    #[test]
+    fn refresh_failure_after_loading_keeps_the_list() {
+        let client = MockClient::new(sample_services());
+        let mut app = App::new(Box::new(client.clone()), Config::default());
+        app.reload();
+
+        client.set_services_error(Some("Failed to connect to bus: Connection reset by peer"));
+        app.reload();
+        assert!(app.diagnostic.is_none());
+        assert_eq!(app.services.len(), 3);
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Failed to connect to bus: Connection reset by peer")
+        );
+
+        // The next refresh is tried on schedule as usual.
+        client.set_services_error(None);
+        app.last_data_tick = Instant::now() - app.data_tick_rate;
+        assert!(app.data_is_stale());
+    }
+
+    #[test]
+    fn missing_journal_is_reported_instead_of_ignored() {
+        let client = MockClient::new(sample_services());
+        client.set_logs_error(Some("journalctl not found"));
+        let mut app = app_with(&client);
+
+        press(&mut app, KeyCode::Char('l'));
+        assert!(!app.showing_logs);
+        assert!(app.journal_unavailable);
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Logs unavailable: journalctl not found")
+        );
+
+        // The message clears on the next key press, the disabled hint stays.
+        press(&mut app, KeyCode::Char('j'));
+        assert_eq!(app.status_message, None);
+        assert!(app.journal_unavailable);
+    }
+
+    #[test]
+    fn boot_failure_view_lists_units_from_the_journal() {
+        let client = MockClient::new(sample_services());
+        client.set_boot_failures(vec![BootFailure {
+            unit: "beta.service".to_string(),
+            time: "2026-10-17T03:04:12+0200".to_string(),
+            result: "exit-code".to_string(),
+            count: 2,
+            last_line: "disk full".to_string(),
+        }]);
+        let mut app = app_with(&client);
+
+        press(&mut app, KeyCode::Char('b'));
+        assert!(app.showing_failures);
+        assert_eq!(app.boot_failures.len(), 1);
+
+        // Actions don't fire while the summary is open.
+        press(&mut app, KeyCode::Char('s'));
+        assert!(client.actions().is_empty());
+
+        press(&mut app, KeyCode::Char('b'));
+        assert!(!app.showing_failures);
+        assert!(app.boot_failures.is_empty());
+    }
+
+    #[test]
+    fn presets_apply_filter_sort_and_scope_then_reset() {
+        let client = MockClient::new(sample_services());
+        let config = Config {
+            presets: vec![
+                Preset {
+                    name: "not-alpha".to_string(),
+                    filter: Some("^(beta|gamma)".to_string()),
+                    sort: SortOrder::State,
+                    scope: Scope::All,
+                },
+                Preset {
+                    name: "mine".to_string(),
+                    filter: None,
+                    sort: SortOrder::Name,
+                    scope: Scope::Config,
+                },
+            ],
+            ..Config::default()
+        };
+        let mut app = App::new(Box::new(client.clone()), config);
+        app.refresh_services().unwrap();
+
+        let view_names = |app: &App| -> Vec<String> {
+            app.get_current_view_services()
+                .into_iter()
+                .map(|s| s.name)
+                .collect()
+        };
+
+        press(&mut app, KeyCode::Char('p'));
+        assert_eq!(app.active_preset, Some(0));
+        // Failed units sort ahead of inactive ones.
+        assert_eq!(view_names(&app), ["gamma.service", "beta.service"]);
+
+        press(&mut app, KeyCode::Char('p'));
+        assert_eq!(view_names(&app), ["alpha.service", "gamma.service"]);
+
+        press(&mut app, KeyCode::Char('p'));
+        assert_eq!(app.active_preset, None);
+        assert!(app.filter.is_none());
+        assert_eq!(view_names(&app), ["alpha.service", "gamma.service"]);
+    }
+
+    #[test]
+    fn search_box_filters_and_reports_bad_regex() {
+        let client = MockClient::new(sample_services());
+        let mut app = app_with(&client);
+        press(&mut app, KeyCode::Tab);
+
+        press(&mut app, KeyCode::Char('/'));
+        for c in "re:^(alpha|beta".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        // While the group is unbalanced the last valid pattern ("re:^") stays applied.
+        assert_eq!(app.get_current_view_services().len(), 3);
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Invalid regex: unclosed group")
+        );
+
+        press(&mut app, KeyCode::Char(')'));
+        assert_eq!(app.status_message, None);
+        assert_eq!(app.get_current_view_services().len(), 2);
+
+        // Keys are typed into the box, not treated as commands.
+        assert!(client.actions().is_empty());
+
+        press(&mut app, KeyCode::Enter);
+        assert!(!app.editing_filter);
+        assert_eq!(app.get_current_view_services().len(), 2);
+
+        // Editing again and escaping clears the filter.
+        press(&mut app, KeyCode::Char('/'));
+        press(&mut app, KeyCode::Esc);
+        assert!(app.filter.is_none());
+        assert_eq!(app.get_current_view_services().len(), 3);
+    }
+
+    #[test]
+    fn fuzzy_search_narrows_the_list() {
+        let client = MockClient::new(sample_services());
+        let mut app = app_with(&client);
+        press(&mut app, KeyCode::Tab);
+
+        press(&mut app, KeyCode::Char('/'));
+        for c in "gma".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+
+        let names: Vec<_> = app
+            .get_current_view_services()
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(names, ["gamma.service"]);
+    }
+
+    #[test]
+    fn hide_inactive_keeps_running_and_failed_units() {
+        let client = MockClient::new(sample_services());
+        let mut app = app_with(&client);
+        press(&mut app, KeyCode::Tab);
+
+        press(&mut app, KeyCode::Char('h'));
+        let names: Vec<_> = app
+            .get_current_view_services()
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(names, ["alpha.service", "gamma.service"]);
+
+        press(&mut app, KeyCode::Char('h'));
+        assert_eq!(app.get_current_view_services().len(), 3);
+    }
+
+    #[test]
+    fn column_chooser_toggles_in_order_and_saves_on_close() {
+        let dir = std::env::temp_dir().join(format!("systemd-tui-monitor-{}", std::process::id()));
+        let path = dir.join("config.toml");
+
+        let client = MockClient::new(sample_services());
+        let mut app = app_with(&client);
+        app.config_path = Some(path.clone());
+
+        press(&mut app, KeyCode::Char('c'));
+        assert!(app.showing_columns);
+
+        // Uptime goes after the existing columns, then Load is dropped.
+        for _ in 0..5 {
+            press(&mut app, KeyCode::Char('j'));
+        }
+        press(&mut app, KeyCode::Char(' '));
+        for _ in 0..5 {
+            press(&mut app, KeyCode::Char('k'));
+        }
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.columns, [Column::SubState, Column::Uptime]);
+
+        // Uptime is only fetched once a column needs it.
+        assert_eq!(client.runtime_requested(), Some(false));
+        app.refresh_services().unwrap();
+        assert_eq!(client.runtime_requested(), Some(true));
+
+        press(&mut app, KeyCode::Esc);
+        assert!(!app.showing_columns);
+        let saved = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(saved.trim(), r#"columns = ["sub-state", "uptime"]"#);
+    }
+
+    #[test]
+    fn dependency_view_jumps_to_filtered_and_unlisted_units() {
+        let client = MockClient::new(sample_services());
+        client.set_dependencies(
+            "alpha.service",
+            &[("Requires", "beta.service"), ("TriggeredBy", "alpha.timer")],
+        );
+        client.set_other_units(vec![service("alpha.timer", "active", "waiting", false)]);
+
+        let mut app = app_with(&client);
+        press(&mut app, KeyCode::Char('h'));
+        assert_eq!(selected_name(&app).as_deref(), Some("alpha.service"));
+
+        // beta is inactive and not user config, so both filters have to go.
+        press(&mut app, KeyCode::Char('d'));
+        assert_eq!(app.dependencies.len(), 2);
+        press(&mut app, KeyCode::Enter);
+        assert!(!app.showing_dependencies);
+        assert_eq!(selected_name(&app).as_deref(), Some("beta.service"));
+        assert!(!app.show_only_user_config && !app.hide_inactive);
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Cleared config-only scope, hide inactive to show beta.service")
+        );
+
+        // The timer isn't a service, so it's loaded on demand and survives a refresh.
+        press(&mut app, KeyCode::Char('k'));
+        press(&mut app, KeyCode::Char('d'));
+        press(&mut app, KeyCode::Char('j'));
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(selected_name(&app).as_deref(), Some("alpha.timer"));
+
+        app.refresh_services().unwrap();
+        assert_eq!(selected_name(&app).as_deref(), Some("alpha.timer"));
+
+        // Moving off it unpins it, so it stops being looked up on every refresh.
+        press(&mut app, KeyCode::Char('k'));
+        app.refresh_services().unwrap();
+        assert!(app.pinned_units.is_empty());
+        assert!(!app.services.iter().any(|s| s.name == "alpha.timer"));
+    }
+
+    #[test]
+    fn jumping_to_an_alias_selects_the_unit_it_names() {
+        let client = MockClient::new(sample_services());
+        client.set_dependencies(
+            "alpha.service",
+            &[("After", "music.service"), ("After", "sockets.target")],
+        );
+        client.set_alias("music.service", "gamma.service");
+        client.set_alias("sockets.target", "basic.target");
+        client.set_other_units(vec![service("basic.target", "active", "active", false)]);
+
+        let mut app = app_with(&client);
+        press(&mut app, KeyCode::Char('h'));
+        press(&mut app, KeyCode::Char('d'));
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(selected_name(&app).as_deref(), Some("gamma.service"));
+        assert!(app.pinned_units.is_empty());
+        assert_eq!(app.services.len(), 3);
+
+        // An unlisted unit is pinned under its own name, so refreshes keep it listed once.
+        press(&mut app, KeyCode::Char('k'));
+        press(&mut app, KeyCode::Char('d'));
+        press(&mut app, KeyCode::Char('j'));
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(selected_name(&app).as_deref(), Some("basic.target"));
+        assert_eq!(app.pinned_units, ["basic.target"]);
+
+        app.refresh_services().unwrap();
+        app.refresh_services().unwrap();
+        assert_eq!(
+            app.services
+                .iter()
+                .filter(|s| s.name == "basic.target")
+                .count(),
+            1
+        );
+        assert_eq!(selected_name(&app).as_deref(), Some("basic.target"));
+    }
+
+    #[test]
+    fn bulk_unit_file_actions_confirm_before_running() {
+        let client = MockClient::new(sample_services());
+        let mut app = app_with(&client);
+
+        // Space marks and moves down, so this marks both user-config units.
+        press(&mut app, KeyCode::Char(' '));
+        press(&mut app, KeyCode::Char(' '));
+        assert_eq!(app.marked.len(), 2);
+
+        press(&mut app, KeyCode::Char('D'));
+        assert_eq!(app.pending_unit_files, Some(UnitFileAction::Disable));
+        assert_eq!(app.pending_changes.len(), 2);
+
+        // Nothing runs until the summary is confirmed.
+        press(&mut app, KeyCode::Esc);
+        assert!(client.unit_file_changes().is_empty());
+        assert_eq!(app.marked.len(), 2);
+
+        press(&mut app, KeyCode::Char('E'));
+        press(&mut app, KeyCode::Char('y'));
+        assert_eq!(
+            client.unit_file_changes(),
+            [(
+                vec!["alpha.service".to_string(), "gamma.service".to_string()],
+                UnitFileAction::Enable
+            )]
+        );
+        assert!(app.marked.is_empty());
+        assert_eq!(app.status_message.as_deref(), Some("Enabled 2 units"));
+
+        // Without marks the selected unit is used.
+        press(&mut app, KeyCode::Char('E'));
+        assert_eq!(app.pending_units, ["alpha.service"]);
+    }
+
+    #[test]
+    fn masking_a_unit_file_in_the_config_dir_is_refused() {
+        let client = MockClient::new(sample_services());
+        let mut app = app_with(&client);
+
+        press(&mut app, KeyCode::Char('M'));
+        assert_eq!(app.pending_unit_files, Some(UnitFileAction::Mask));
+        assert!(app.unit_file_action_blocked());
+
+        // Confirming does nothing while systemctl would refuse; only cancelling closes it.
+        press(&mut app, KeyCode::Char('y'));
+        assert!(client.unit_file_changes().is_empty());
+        assert_eq!(app.pending_unit_files, Some(UnitFileAction::Mask));
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.pending_unit_files, None);
+    }
+
+    #[test]
+    fn notify_rules_alert_on_matching_failures_only() {
+        let client = MockClient::new(sample_services());
+        let config = Config {
+            notify: vec![NotifyRule {
+                unit: "g*".to_string(),
+                on_failure: true,
+                on_restart: false,
+                memory_max: None,
+            }],
+            ..Config::default()
+        };
+        let mut app = App::new(Box::new(client.clone()), config);
+        app.refresh_services().unwrap();
+
+        client.set_services(vec![
+            service("alpha.service", "failed", "failed", true),
+            service("beta.service", "inactive", "dead", false),
+            service("gamma.service", "active", "running", true),
+        ]);
+        app.refresh_services().unwrap();
+        assert!(client.notifications().is_empty());
+
+        client.set_services(sample_services());
+        app.refresh_services().unwrap();
+        assert_eq!(client.notifications(), ["gamma.service failed"]);
+        assert_eq!(app.status_message.as_deref(), Some("gamma.service failed"));
+    }
+
+    #[test]
+    fn scrolling_past_the_top_pages_in_older_logs() {
+        let client = MockClient::new(sample_services());
+        client.set_logs((0..70_000).map(|i| format!("line {i}")).collect());
+        let mut app = app_with(&client);
+
+        press(&mut app, KeyCode::Char('l'));
+        assert_eq!(app.logs.len(), LOG_PAGE);
+        assert_eq!(app.logs[0], "line 69900");
+
+        press(&mut app, KeyCode::Char('k'));
+        assert_eq!(app.logs.len(), 2 * LOG_PAGE);
+        assert_eq!(app.logs[app.log_scroll], "line 69899");
+
+        // Each page is read back from the oldest line, not the whole tail again.
+        app.log_scroll = 0;
+        let calls = client.calls();
+        press(&mut app, KeyCode::Char('k'));
+        assert_eq!(client.calls(), calls + 1);
+        assert_eq!(app.logs[app.log_scroll], "line 69799");
+        assert_eq!(app.log_oldest.as_deref(), Some("69700"));
+
+        // Offsets past u16::MAX still scroll.
+        app.logs = (50..70_000).map(|i| format!("line {i}")).collect();
+        app.log_oldest = Some("50".to_string());
+        app.log_scroll = 0;
+        press(&mut app, KeyCode::Char('k'));
+        assert_eq!(app.logs.len(), 70_000);
+        assert_eq!(app.log_scroll, 49);
+        app.log_scroll = 66_048;
+        press(&mut app, KeyCode::Char('j'));
+        assert_eq!(app.logs[app.log_scroll], "line 66049");
+
+        // Once everything is loaded, scrolling up at the top just says so.
+        app.log_scroll = 0;
+        press(&mut app, KeyCode::Char('k'));
+        assert_eq!(app.logs.len(), 70_000);
+        assert_eq!(app.status_message.as_deref(), Some("Start of log"));
+    }
+
+    #[test]
+    fn live_logs_only_fetch_new_lines() {
+        let client = MockClient::new(sample_services());
+        client.set_logs((0..300).map(|i| format!("line {i}")).collect());
+        let mut app = app_with(&client);
+
+        press(&mut app, KeyCode::Char('l'));
+        press(&mut app, KeyCode::Char('k'));
+        assert_eq!(app.logs.len(), 2 * LOG_PAGE);
+
+        // Paged-in history stays put while new lines are added below it.
+        client.set_logs((0..305).map(|i| format!("line {i}")).collect());
+        app.refresh_live_logs(40);
+        assert_eq!(app.logs.len(), 2 * LOG_PAGE + 5);
+        assert_eq!(app.logs[0], "line 100");
+        assert_eq!(app.logs.last().map(String::as_str), Some("line 304"));
+        assert_eq!(app.log_cursor.as_deref(), Some("304"));
+
+        app.refresh_live_logs(40);
+        assert_eq!(app.logs.len(), 2 * LOG_PAGE + 5);
+    }
+
+    #[test]
+    fn following_logs_keeps_a_bounded_tail() {
+        let client = MockClient::new(sample_services());
+        client.set_logs((0..LOG_PAGE).map(|i| format!("line {i}")).collect());
+        let mut app = app_with(&client);
+        press(&mut app, KeyCode::Char('l'));
+        press(&mut app, KeyCode::Char('k'));
+        app.log_scroll = 60;
+
+        client.set_logs((0..LOG_BUFFER + 50).map(|i| format!("line {i}")).collect());
+        app.stick_to_bottom = false;
+        app.refresh_live_logs(40);
+        assert_eq!(app.logs.len(), LOG_BUFFER);
+        assert_eq!(app.logs[0], "line 50");
+        // The view stays on the same line, as far as it still can.
+        assert_eq!(app.log_scroll, 10);
+        assert_eq!(app.log_oldest, None);
+
+        // Paging up walks back to the new top, then carries on from there.
+        app.log_scroll = 0;
+        press(&mut app, KeyCode::Char('k'));
+        assert_eq!(app.logs[0], "line 0");
+        assert_eq!(app.logs[app.log_scroll], "line 49");
+    }
+
+    #[test]
+    fn journal_usage_reports_the_units_share() {
+        let client = MockClient::new(sample_services());
+        client.set_logs(vec!["started".to_string()]);
+        let mut app = app_with(&client);
+        press(&mut app, KeyCode::Char('l'));
+
+        press(&mut app, KeyCode::Char('V'));
+        let usage = app.journal_usage.as_ref().unwrap();
+        assert_eq!((usage.unit_entries, usage.total_entries), (1, 10));
+        press(&mut app, KeyCode::Esc);
+        assert!(app.journal_usage.is_none() && app.showing_logs);
+    }
+
+    #[test]
+    fn documentation_key_opens_single_links_and_lists_several() {
+        let client = MockClient::new(sample_services());
+        client.set_documentation("alpha.service", &["man:alpha(1)"]);
+        client.set_documentation("gamma.service", &["man:gamma(8)", "https://gamma.example/"]);
+        let mut app = app_with(&client);
+
+        press(&mut app, KeyCode::Char('o'));
+        assert_eq!(app.pending_doc.as_deref(), Some("man:alpha(1)"));
+        assert!(!app.showing_docs);
+        app.pending_doc = None;
+
+        press(&mut app, KeyCode::Char('j'));
+        press(&mut app, KeyCode::Char('o'));
+        assert!(app.showing_docs);
+        press(&mut app, KeyCode::Char('j'));
+        press(&mut app, KeyCode::Enter);
+        assert!(!app.showing_docs);
+        assert_eq!(app.pending_doc.as_deref(), Some("https://gamma.example/"));
+
+        client.set_documentation("gamma.service", &[]);
+        press(&mut app, KeyCode::Char('o'));
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("gamma.service has no Documentation=")
+        );
+    }
+
+    #[test]
+    fn machines_view_controls_machines_and_shows_their_journal() {
+        let client = MockClient::new(sample_services());
+        client.set_logs(vec!["booted".to_string()]);
+        client.set_machines(vec![
+            Machine {
+                name: "archlinux".to_string(),
+                class: String::new(),
+                os: String::new(),
+                running: false,
+            },
+            Machine {
+                name: "fedora-dev".to_string(),
+                class: "container".to_string(),
+                os: "fedora".to_string(),
+                running: true,
+            },
+        ]);
+        let mut app = app_with(&client);
+
+        press(&mut app, KeyCode::Char('m'));
+        assert_eq!(app.machines.len(), 2);
+
+        // s/x mean start/stop here, not unit actions.
+        press(&mut app, KeyCode::Char('s'));
+        press(&mut app, KeyCode::Char('j'));
+        press(&mut app, KeyCode::Char('t'));
+        assert_eq!(
+            client.machine_actions(),
+            [
+                ("archlinux".to_string(), MachineAction::Start),
+                ("fedora-dev".to_string(), MachineAction::Terminate),
+            ]
+        );
+        assert!(client.actions().is_empty());
+
+        press(&mut app, KeyCode::Char('l'));
+        assert_eq!(app.logs, ["fedora-dev: booted"]);
+        press(&mut app, KeyCode::Char('k'));
+        assert_eq!(app.status_message.as_deref(), Some("Start of log"));
+
+        // Closing the journal goes back to the machine list.
+        press(&mut app, KeyCode::Esc);
+        assert!(app.showing_machines && app.log_machine.is_none());
+        press(&mut app, KeyCode::Esc);
+        assert!(!app.showing_machines);
+    }
+
+    #[test]
+    fn recorded_session_replays_to_the_same_state() {
+        let file = recording::TempRecording::new("session");
+
+        let client = MockClient::new(sample_services());
+        client.set_logs(vec!["started".to_string(), "ready".to_string()]);
+        client.set_documentation("alpha.service", &["man:alpha(1)"]);
+        let recorder = Recorder::create(&file.0, "").unwrap();
+        let mut app = App::new(
+            Box::new(recording::RecordingClient::new(
+                Box::new(client.clone()),
+                recorder.clone(),
+            )),
+            Config::default(),
+        );
+        app.set_recorder(recorder);
+
+        // Keys go through the same path as in `run`, which records them.
+        app.reload();
+        let key = |app: &mut App, code| app.on_key(KeyEvent::from(code)).unwrap();
+        key(&mut app, KeyCode::Char('o'));
+        if let Some(uri) = app.pending_doc.take() {
+            app.open_documentation(&uri);
+        }
+        key(&mut app, KeyCode::Char('j'));
+        key(&mut app, KeyCode::Char('l'));
+        app.refresh_live_logs(40);
+        key(&mut app, KeyCode::Esc);
+        key(&mut app, KeyCode::Char('r'));
+        assert_eq!(client.opened_docs(), ["man:alpha(1)"]);
+        drop(app);
+
+        let live_calls = client.calls();
+        let replay = Replay::load(&file.0).unwrap();
+        let mut replayed = App::new(Box::new(replay.client()), replay.config().unwrap());
+        while replayed.replay_step(&replay, 40).unwrap() {}
+
+        assert_eq!(replayed.services.len(), 3);
+        assert_eq!(selected_name(&replayed).as_deref(), Some("gamma.service"));
+        assert!(!replayed.showing_logs && replayed.pending_doc.is_none());
+        assert_eq!(replayed.status_message, None);
+        // Nothing was re-run for real: not the restart, and not the man page.
+        assert_eq!(client.calls(), live_calls);
+        assert_eq!(client.actions().len(), 1);
+        assert_eq!(client.opened_docs().len(), 1);
+    }
+}
@@ -0,0 +1,164 @@
+// Decides which unit changes deserve a notification, based on the config's rules.
+
+use super::config::NotifyRule;
+use super::format::format_bytes;
+use super::model::Service;
+
+/// The first rule whose pattern matches, so specific units can be listed before globs.
+fn rule_for<'a>(rules: &'a [NotifyRule], unit: &str) -> Option<&'a NotifyRule> {
+    rules.iter().find(|rule| glob_match(&rule.unit, unit))
+}
+
+/// Compares two refreshes and describes what changed for units that have a rule.
+pub fn detect(rules: &[NotifyRule], old: &[Service], new: &[Service]) -> Vec<String> {
+    let mut alerts = Vec::new();
+
+    for service in new {
+        let Some(rule) = rule_for(rules, &service.name) else {
+            continue;
+        };
+        // Units appearing for the first time have nothing to compare against.
+        let Some(previous) = old.iter().find(|s| s.name == service.name) else {
+            continue;
+        };
+
+        if rule.on_failure && service.active_state == "failed" && previous.active_state != "failed"
+        {
+            alerts.push(format!("{} failed", service.name));
+        }
+
+        if rule.on_restart {
+            if let (Some(before), Some(after)) = (previous.active_since, service.active_since) {
+                if after > before {
+                    alerts.push(format!("{} restarted", service.name));
+                }
+            }
+        }
+
+        // Only alert when crossing the limit, not on every refresh above it.
+        if let (Some(limit), Some(bytes)) = (rule.memory_max, service.memory_bytes) {
+            if bytes > limit && previous.memory_bytes.is_none_or(|before| before <= limit) {
+                alerts.push(format!(
+                    "{} is using {} (limit {})",
+                    service.name,
+                    format_bytes(bytes),
+                    format_bytes(limit)
+                ));
+            }
+        }
+    }
+
+    alerts
+}
+
+/// Shell-style matching where `*` is any run of characters and `?` is exactly one.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    let (mut p, mut n) = (0, 0);
+    // Where to resume after the last `*` if the rest fails to match.
+    let mut backtrack = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    n = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::systemd::mock::service;
+
+    fn rule(unit: &str) -> NotifyRule {
+        NotifyRule {
+            unit: unit.to_string(),
+            on_failure: true,
+            on_restart: false,
+            memory_max: None,
+        }
+    }
+
+    #[test]
+    fn globs_match_whole_names() {
+        assert!(glob_match("backup-*.service", "backup-daily.service"));
+        assert!(glob_match("*", "anything.service"));
+        assert!(glob_match("app?.service", "app1.service"));
+        assert!(!glob_match("backup-*.service", "backup.service"));
+        assert!(!glob_match("mpd", "mpd.service"));
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let rules = [
+            NotifyRule {
+                on_failure: false,
+                ..rule("noisy.service")
+            },
+            rule("*"),
+        ];
+
+        let old = [
+            service("noisy.service", "active", "running", true),
+            service("mpd.service", "active", "running", true),
+        ];
+        let new = [
+            service("noisy.service", "failed", "failed", true),
+            service("mpd.service", "failed", "failed", true),
+        ];
+
+        assert_eq!(detect(&rules, &old, &new), ["mpd.service failed"]);
+        // Still failed on the next refresh is not a new failure.
+        assert!(detect(&rules, &new, &new).is_empty());
+    }
+
+    #[test]
+    fn restarts_and_memory_limits() {
+        let rules = [NotifyRule {
+            on_failure: false,
+            on_restart: true,
+            memory_max: Some(100 * 1024 * 1024),
+            ..rule("mpd.service")
+        }];
+
+        let before = Service {
+            active_since: Some(1000),
+            memory_bytes: Some(50 * 1024 * 1024),
+            ..service("mpd.service", "active", "running", true)
+        };
+        let after = Service {
+            active_since: Some(2000),
+            memory_bytes: Some(150 * 1024 * 1024),
+            ..before.clone()
+        };
+
+        assert_eq!(
+            detect(&rules, &[before], std::slice::from_ref(&after)),
+            [
+                "mpd.service restarted",
+                "mpd.service is using 150.0M (limit 100.0M)"
+            ]
+        );
+        let after = [after];
+        assert!(detect(&rules, &after, &after).is_empty());
+    }
+}
@@ -0,0 +1,315 @@
+// Loads user settings from ~/.config/systemd-tui-monitor/config.toml.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Deserializer};
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+use toml_edit::{Array, DocumentMut};
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub presets: Vec<Preset>,
+    /// Extra columns shown after the unit name, in display order.
+    pub columns: Vec<Column>,
+    /// Which units are worth a desktop notification, and for what.
+    pub notify: Vec<NotifyRule>,
+    /// Where the config was read from, so column changes can be written back.
+    #[serde(skip)]
+    pub path: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            presets: Vec::new(),
+            columns: vec![Column::Load, Column::SubState],
+            notify: Vec::new(),
+            path: None,
+        }
+    }
+}
+
+/// A named view that can be cycled to with a single key.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Preset {
+    pub name: String,
+    /// Regex matched against unit names and descriptions.
+    #[serde(default)]
+    pub filter: Option<String>,
+    #[serde(default)]
+    pub sort: SortOrder,
+    #[serde(default)]
+    pub scope: Scope,
+}
+
+/// Alerts for units matching `unit`, a name or a glob like "backup-*.service".
+#[derive(Debug, Clone, Deserialize)]
+pub struct NotifyRule {
+    pub unit: String,
+    #[serde(default = "default_true")]
+    pub on_failure: bool,
+    #[serde(default)]
+    pub on_restart: bool,
+    /// Written like systemd's MemoryMax=, e.g. "512M" or "2G".
+    #[serde(default, deserialize_with = "deserialize_bytes")]
+    pub memory_max: Option<u64>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn deserialize_bytes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    parse_bytes(&value)
+        .map(Some)
+        .ok_or_else(|| serde::de::Error::custom(format!("invalid size \"{}\"", value)))
+}
+
+/// Parses a byte count with an optional K/M/G/T suffix (powers of 1024).
+fn parse_bytes(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let (number, multiplier) = match value.chars().last()?.to_ascii_uppercase() {
+        'K' => (&value[..value.len() - 1], 1u64 << 10),
+        'M' => (&value[..value.len() - 1], 1 << 20),
+        'G' => (&value[..value.len() - 1], 1 << 30),
+        'T' => (&value[..value.len() - 1], 1 << 40),
+        _ => (value, 1),
+    };
+    number.trim().parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SortOrder {
+    #[default]
+    Name,
+    /// Failed units first, then active ones, then everything else.
+    State,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Scope {
+    /// Only units defined in ~/.config/systemd/user.
+    #[default]
+    Config,
+    All,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Column {
+    Load,
+    State,
+    SubState,
+    Enablement,
+    Memory,
+    Uptime,
+    Description,
+}
+
+impl Column {
+    /// Every column, in the order they're displayed.
+    pub const ALL: [Column; 7] = [
+        Column::Load,
+        Column::State,
+        Column::SubState,
+        Column::Enablement,
+        Column::Memory,
+        Column::Uptime,
+        Column::Description,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Column::Load => "Load",
+            Column::State => "State",
+            Column::SubState => "Sub-state",
+            Column::Enablement => "Enablement",
+            Column::Memory => "Memory",
+            Column::Uptime => "Uptime",
+            Column::Description => "Description",
+        }
+    }
+
+    /// The name used in config.toml.
+    fn key(self) -> &'static str {
+        match self {
+            Column::Load => "load",
+            Column::State => "state",
+            Column::SubState => "sub-state",
+            Column::Enablement => "enablement",
+            Column::Memory => "memory",
+            Column::Uptime => "uptime",
+            Column::Description => "description",
+        }
+    }
+}
+
+pub fn path() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("systemd-tui-monitor/config.toml"))
+}
+
+/// Reads the config file, falling back to defaults if it doesn't exist.
+pub fn load() -> Result<Config> {
+    let Some(path) = path() else {
+        return Ok(Config::default());
+    };
+    if !path.exists() {
+        return Ok(Config {
+            path: Some(path),
+            ..Config::default()
+        });
+    }
+
+    let contents =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut config =
+        parse(&contents).with_context(|| format!("Invalid config in {}", path.display()))?;
+    config.path = Some(path);
+    Ok(config)
+}
+
+/// Writes the column choice back, keeping the rest of the file (and its comments) as-is.
+pub fn save_columns(path: &Path, columns: &[Column]) -> Result<()> {
+    let contents = if path.exists() {
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?
+    } else {
+        String::new()
+    };
+
+    let updated = with_columns(&contents, columns)
+        .with_context(|| format!("Invalid config in {}", path.display()))?;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    fs::write(path, updated).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn with_columns(contents: &str, columns: &[Column]) -> Result<String> {
+    let mut doc: DocumentMut = contents.parse()?;
+    doc["columns"] = toml_edit::value(columns.iter().map(|c| c.key()).collect::<Array>());
+    Ok(doc.to_string())
+}
+
+/// The raw config file, or nothing if there isn't one, for embedding in recordings.
+pub fn source() -> String {
+    path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .unwrap_or_default()
+}
+
+pub fn parse(contents: &str) -> Result<Config> {
+    let config: Config = toml::from_str(contents)?;
+
+    // Catch bad patterns now rather than when the preset is first selected.
+    for preset in &config.presets {
+        if let Some(filter) = &preset.filter {
+            Regex::new(filter)
+                .with_context(|| format!("Preset \"{}\" has an invalid filter", preset.name))?;
+        }
+    }
+
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_presets_with_defaults() {
+        let config = parse(
+            r#"
+            [[presets]]
+            name = "my-daemons"
+            filter = "^(mpd|syncthing)"
+            sort = "state"
+            scope = "all"
+
+            [[presets]]
+            name = "everything"
+            scope = "all"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.presets.len(), 2);
+        assert_eq!(
+            config.presets[0].filter.as_deref(),
+            Some("^(mpd|syncthing)")
+        );
+        assert_eq!(config.presets[0].sort, SortOrder::State);
+        assert_eq!(config.presets[1].filter, None);
+        assert_eq!(config.presets[1].sort, SortOrder::Name);
+        assert_eq!(config.presets[1].scope, Scope::All);
+    }
+
+    #[test]
+    fn empty_file_is_valid() {
+        let config = parse("").unwrap();
+        assert!(config.presets.is_empty());
+        assert_eq!(config.columns, [Column::Load, Column::SubState]);
+    }
+
+    #[test]
+    fn parses_notify_rules() {
+        let config = parse(
+            r#"
+            [[notify]]
+            unit = "backup-*.service"
+            on_restart = true
+            memory_max = "512M"
+
+            [[notify]]
+            unit = "*"
+            on_failure = false
+            "#,
+        )
+        .unwrap();
+
+        let backup = &config.notify[0];
+        assert!(backup.on_failure && backup.on_restart);
+        assert_eq!(backup.memory_max, Some(512 * 1024 * 1024));
+        assert!(!config.notify[1].on_failure);
+        assert_eq!(config.notify[1].memory_max, None);
+
+        let err = parse("[[notify]]\nunit = \"*\"\nmemory_max = \"lots\"\n").unwrap_err();
+        assert!(err.to_string().contains("invalid size \"lots\""));
+    }
+
+    #[test]
+    fn saving_columns_keeps_presets_and_comments() {
+        let original = r#"# my views
+[[presets]]
+name = "everything"
+scope = "all"
+"#;
+        let updated = with_columns(original, &[Column::Memory, Column::Uptime]).unwrap();
+
+        assert!(updated.contains("# my views"));
+        let config = parse(&updated).unwrap();
+        assert_eq!(config.presets.len(), 1);
+        assert_eq!(config.columns, [Column::Memory, Column::Uptime]);
+    }
+
+    #[test]
+    fn rejects_invalid_filter() {
+        let err = parse(
+            r#"
+            [[presets]]
+            name = "broken"
+            filter = "backup-("
+            "#,
+        )
+        .unwrap_err();
+        assert!(format!("{:#}", err).contains("\"broken\""));
+    }
+}
@@ -0,0 +1,141 @@
+// Turns "systemctl --user" failures into an explanation the user can act on.
+
+use std::env;
+use std::io;
+
+/// A full-screen explanation shown instead of the service list.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub title: String,
+    pub cause: String,
+    pub fixes: Vec<String>,
+    /// The raw error, for bug reports.
+    pub detail: String,
+}
+
+/// Works out the most likely reason the user manager couldn't be reached.
+pub fn diagnose(err: &anyhow::Error) -> Diagnostic {
+    let systemctl_missing = err
+        .chain()
+        .filter_map(|e| e.downcast_ref::<io::Error>())
+        .any(|e| e.kind() == io::ErrorKind::NotFound);
+    let has_session_env = env::var_os("XDG_RUNTIME_DIR").is_some()
+        || env::var_os("DBUS_SESSION_BUS_ADDRESS").is_some();
+
+    classify(&format!("{:#}", err), systemctl_missing, has_session_env)
+}
+
+fn classify(detail: &str, systemctl_missing: bool, has_session_env: bool) -> Diagnostic {
+    let (title, cause, fixes): (&str, &str, &[&str]) = if systemctl_missing {
+        (
+            "systemctl not found",
+            "The `systemctl` binary is not on your PATH, so systemd doesn't appear to be installed.",
+            &[
+                "This monitor only works on systemd-based systems.",
+                "If systemd is installed in an unusual location, add it to PATH.",
+            ],
+        )
+    } else if !has_session_env {
+        (
+            "No user session bus",
+            "Neither XDG_RUNTIME_DIR nor DBUS_SESSION_BUS_ADDRESS is set. This usually happens over \
+             ssh, su or sudo, where no login session was registered with systemd-logind.",
+            &[
+                "export XDG_RUNTIME_DIR=/run/user/$(id -u) and try again.",
+                "Use `machinectl shell $USER@` instead of su/sudo to get a full session.",
+                "Make sure pam_systemd is enabled for the login method you're using.",
+            ],
+        )
+    } else if detail.contains("$DBUS_SESSION_BUS_ADDRESS") {
+        (
+            "Session bus unreachable",
+            "XDG_RUNTIME_DIR or DBUS_SESSION_BUS_ADDRESS is set, but systemctl couldn't use it to \
+             find the session bus.",
+            &[
+                "Check that XDG_RUNTIME_DIR is /run/user/$(id -u) and that the directory exists.",
+                "After su or sudo these can still point at the previous user's session.",
+                "Run `systemctl --user status` yourself to see the same error.",
+            ],
+        )
+    } else if detail.contains("Failed to connect to bus") {
+        (
+            "User manager not running",
+            "The session environment is set up, but there is no `systemd --user` instance listening \
+             on it.",
+            &[
+                "Run `loginctl enable-linger $USER` so your user manager starts at boot.",
+                "Check `systemctl status user@$(id -u).service` for why it stopped.",
+                "Containers frequently run without systemd as PID 1.",
+            ],
+        )
+    } else {
+        (
+            "systemctl failed",
+            "`systemctl --user list-units` returned an error.",
+            &["Run `systemctl --user list-units` yourself to see the full output."],
+        )
+    };
+
+    Diagnostic {
+        title: title.to_string(),
+        cause: cause.to_string(),
+        fixes: fixes.iter().map(|s| s.to_string()).collect(),
+        detail: detail.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_binary_wins() {
+        let d = classify("No such file or directory", true, true);
+        assert_eq!(d.title, "systemctl not found");
+    }
+
+    #[test]
+    fn ssh_without_session_env() {
+        let d = classify(
+            "systemctl returned non-zero status: Failed to connect to bus: No medium found",
+            false,
+            false,
+        );
+        assert_eq!(d.title, "No user session bus");
+
+        let d = classify(
+            "Failed to connect to bus: $DBUS_SESSION_BUS_ADDRESS and $XDG_RUNTIME_DIR not defined",
+            false,
+            false,
+        );
+        assert_eq!(d.title, "No user session bus");
+    }
+
+    #[test]
+    fn session_env_set_but_unusable() {
+        let d = classify(
+            "Failed to connect to bus: $DBUS_SESSION_BUS_ADDRESS and $XDG_RUNTIME_DIR not defined",
+            false,
+            true,
+        );
+        assert_eq!(d.title, "Session bus unreachable");
+        assert!(!d.cause.starts_with("Neither"));
+    }
+
+    #[test]
+    fn session_env_but_no_manager() {
+        let d = classify(
+            "systemctl returned non-zero status: Failed to connect to bus: No such file or directory",
+            false,
+            true,
+        );
+        assert_eq!(d.title, "User manager not running");
+    }
+
+    #[test]
+    fn unknown_errors_keep_the_detail() {
+        let d = classify("something odd", false, true);
+        assert_eq!(d.title, "systemctl failed");
+        assert_eq!(d.detail, "something odd");
+    }
+}
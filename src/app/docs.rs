@@ -0,0 +1,128 @@
+// Opens the man pages and URLs a unit lists in Documentation=.
+
+use anyhow::{Context, Result};
+use crossterm::{
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+};
+use std::io;
+use std::process::{Command, Stdio};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum DocCommand {
+    /// Arguments for `man`, which needs the terminal to itself.
+    Man(Vec<String>),
+    /// Anything xdg-open can hand to a browser.
+    Url(String),
+}
+
+/// `man:systemd.service(5)` becomes `man 5 systemd.service`; web and file links go to xdg-open.
+pub fn command_for(uri: &str) -> Option<DocCommand> {
+    if let Some(page) = uri.strip_prefix("man:") {
+        let args = match page.strip_suffix(')').and_then(|p| p.split_once('(')) {
+            Some((name, section)) => vec![section.to_string(), name.to_string()],
+            None => vec![page.to_string()],
+        };
+        return Some(DocCommand::Man(args));
+    }
+
+    ["http://", "https://", "file:"]
+        .iter()
+        .any(|scheme| uri.starts_with(scheme))
+        .then(|| DocCommand::Url(uri.to_string()))
+}
+
+/// Gives the terminal back to the TUI when dropped, however running the command went.
+struct SuspendedTui;
+
+impl SuspendedTui {
+    fn new() -> Result<Self> {
+        // Created first, so a failure halfway through leaving still restores what was undone.
+        let guard = SuspendedTui;
+        disable_raw_mode()?;
+        execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture)?;
+        Ok(guard)
+    }
+}
+
+impl Drop for SuspendedTui {
+    fn drop(&mut self) {
+        let _ = enable_raw_mode();
+        let _ = execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture);
+    }
+}
+
+/// Leaves the TUI while `man` runs, then restores it. The caller must redraw afterwards.
+pub fn run_man(args: &[String]) -> Result<()> {
+    let output = {
+        let _suspended = SuspendedTui::new()?;
+        // Only stderr is captured; the page itself goes straight to the terminal.
+        Command::new("man")
+            .args(args)
+            .stderr(Stdio::piped())
+            .spawn()
+            .and_then(|child| child.wait_with_output())
+            .context("Failed to execute man")?
+    };
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(anyhow::anyhow!(
+            "man {} failed: {}",
+            args.join(" "),
+            stderr.trim()
+        ))
+    }
+}
+
+/// Hands a URL to the desktop without waiting for the browser.
+pub fn open_url(url: &str) -> Result<()> {
+    let mut child = Command::new("xdg-open")
+        .arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("Failed to execute xdg-open")?;
+    // Reaped on its own thread so it doesn't linger as a zombie.
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn man_pages_with_and_without_sections() {
+        assert_eq!(
+            command_for("man:systemd.service(5)"),
+            Some(DocCommand::Man(vec![
+                "5".to_string(),
+                "systemd.service".to_string()
+            ]))
+        );
+        assert_eq!(
+            command_for("man:syncthing"),
+            Some(DocCommand::Man(vec!["syncthing".to_string()]))
+        );
+    }
+
+    #[test]
+    fn urls_go_to_xdg_open_and_others_are_unsupported() {
+        assert_eq!(
+            command_for("https://docs.syncthing.net/"),
+            Some(DocCommand::Url("https://docs.syncthing.net/".to_string()))
+        );
+        assert_eq!(
+            command_for("file:/usr/share/doc/mpd/index.html"),
+            Some(DocCommand::Url(
+                "file:/usr/share/doc/mpd/index.html".to_string()
+            ))
+        );
+        assert_eq!(command_for("info:coreutils"), None);
+    }
+}
@@ -0,0 +1,115 @@
+// Matches units against the search box and preset filters.
+
+use super::model::Service;
+use regex::Regex;
+
+const REGEX_PREFIX: &str = "re:";
+
+#[derive(Debug, Clone)]
+pub enum Filter {
+    /// Case-insensitive subsequence match, so "sthg" finds "syncthing".
+    Fuzzy(String),
+    Regex(Regex),
+}
+
+impl Filter {
+    /// `re:<pattern>` is a regex, anything else is fuzzy. Empty input means no filter.
+    pub fn parse(query: &str) -> Result<Option<Filter>, regex::Error> {
+        if let Some(pattern) = query.strip_prefix(REGEX_PREFIX) {
+            return Regex::new(pattern).map(|re| Some(Filter::Regex(re)));
+        }
+
+        if query.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(Filter::Fuzzy(query.to_lowercase())))
+        }
+    }
+
+    /// The search box text that produces a regex filter.
+    pub fn regex_query(pattern: &str) -> String {
+        format!("{}{}", REGEX_PREFIX, pattern)
+    }
+
+    /// The search box text this filter was parsed from (lowercased for fuzzy filters).
+    pub fn query(&self) -> String {
+        match self {
+            Filter::Fuzzy(needle) => needle.clone(),
+            Filter::Regex(re) => Self::regex_query(re.as_str()),
+        }
+    }
+
+    pub fn matches(&self, service: &Service) -> bool {
+        match self {
+            Filter::Fuzzy(needle) => {
+                fuzzy_match(&service.name, needle) || fuzzy_match(&service.description, needle)
+            }
+            Filter::Regex(re) => re.is_match(&service.name) || re.is_match(&service.description),
+        }
+    }
+}
+
+/// The status bar has one line, but regex errors span several with the cause last.
+pub fn describe_error(err: &regex::Error) -> String {
+    let message = err.to_string();
+    let cause = message.lines().last().unwrap_or_default();
+    format!("Invalid regex: {}", cause.trim_start_matches("error: "))
+}
+
+fn fuzzy_match(haystack: &str, needle: &str) -> bool {
+    let mut haystack = haystack.chars().flat_map(char::to_lowercase);
+    needle.chars().all(|c| haystack.any(|h| h == c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::systemd::mock::service;
+
+    fn described(name: &str, description: &str) -> Service {
+        Service {
+            description: description.to_string(),
+            ..service(name, "active", "running", true)
+        }
+    }
+
+    #[test]
+    fn fuzzy_matches_subsequences_case_insensitively() {
+        let filter = Filter::parse("SThg").unwrap().unwrap();
+        assert!(filter.matches(&described("syncthing.service", "")));
+        assert!(!filter.matches(&described("mpd.service", "")));
+
+        let filter = Filter::parse("music").unwrap().unwrap();
+        assert!(filter.matches(&described("mpd.service", "Music Player Daemon")));
+    }
+
+    #[test]
+    fn regex_matches_names_and_descriptions() {
+        let filter = Filter::parse(r"re:backup-.*\.service").unwrap().unwrap();
+        assert!(filter.matches(&described("backup-daily.service", "")));
+        assert!(!filter.matches(&described("backup.service", "")));
+
+        let filter = Filter::parse("re:^Music").unwrap().unwrap();
+        assert!(filter.matches(&described("mpd.service", "Music Player Daemon")));
+    }
+
+    #[test]
+    fn query_round_trips() {
+        for query in ["sthg", r"re:^(mpd|sync)"] {
+            assert_eq!(Filter::parse(query).unwrap().unwrap().query(), query);
+        }
+    }
+
+    #[test]
+    fn empty_query_clears_the_filter() {
+        assert!(Filter::parse("").unwrap().is_none());
+        // An empty regex is still a (match-everything) filter.
+        assert!(Filter::parse("re:").unwrap().is_some());
+    }
+
+    #[test]
+    fn invalid_regex_is_an_error_not_a_panic() {
+        let err = Filter::parse("re:backup-(").unwrap_err();
+        assert_eq!(describe_error(&err), "Invalid regex: unclosed group");
+    }
+}
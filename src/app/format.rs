@@ -0,0 +1,47 @@
+// Turns sizes and durations into the short strings shown in columns and alerts.
+
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["K", "M", "G", "T"];
+
+    if bytes < 1024 {
+        return format!("{}B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1}{}", value, UNITS[unit])
+}
+
+/// The two most significant parts, e.g. "3d 4h" or "5m 12s".
+pub fn format_duration(secs: u64) -> String {
+    let (days, hours, minutes) = (secs / 86400, secs / 3600 % 24, secs / 60 % 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m {}s", minutes, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_memory_and_uptime() {
+        assert_eq!(format_bytes(512), "512B");
+        assert_eq!(format_bytes(5 * 1024 * 1024), "5.0M");
+        assert_eq!(format_bytes(3 * 1024 * 1024 * 1024 / 2), "1.5G");
+
+        assert_eq!(format_duration(42), "42s");
+        assert_eq!(format_duration(5 * 60 + 12), "5m 12s");
+        assert_eq!(format_duration(2 * 3600 + 5 * 60), "2h 5m");
+        assert_eq!(format_duration(3 * 86400 + 4 * 3600 + 59), "3d 4h");
+    }
+}
@@ -1,12 +1,19 @@
 // Defines the core data structures for the application.
 
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
 /// Represents the status of a systemd service.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Service {
     pub name: String,
     pub active_state: String, // e.g., "active", "inactive"
     pub sub_state: String,    // e.g., "running", "dead", "exited"
     pub loaded_state: String, // e.g., "loaded", "not-found"
+    pub description: String,  // empty for units that aren't loaded
+    pub enablement: String,   // e.g., "enabled", "static"; empty for transient units
+    pub memory_bytes: Option<u64>,
+    pub active_since: Option<u64>, // unix seconds, only for active units
     pub is_user_config: bool,
 }
 
@@ -14,4 +21,70 @@ impl Service {
     pub fn is_running(&self) -> bool {
         self.active_state == "active" && self.sub_state == "running"
     }
+
+    /// Stopped or never loaded, which covers the placeholders added from list-unit-files.
+    /// Failed units are deliberately not inactive.
+    pub fn is_inactive(&self) -> bool {
+        self.active_state == "inactive"
+    }
+}
+
+/// A link from one unit to another, e.g. `After=dbus.socket`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Relation {
+    pub kind: String, // the property name, e.g., "Requires", "TriggeredBy"
+    pub unit: String,
+}
+
+/// Lines read from a journal, and where to carry on reading from.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogChunk {
+    pub lines: Vec<String>,
+    /// None for logs read from a file, which has no cursor.
+    pub cursor: Option<String>,
+}
+
+/// A symlink that enabling, disabling or masking a unit adds or removes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SymlinkChange {
+    Create {
+        link: PathBuf,
+        target: PathBuf,
+    },
+    Remove {
+        link: PathBuf,
+    },
+    /// A link systemctl will refuse to make, so the whole action would fail.
+    Blocked {
+        link: PathBuf,
+        reason: String,
+    },
+}
+
+/// A systemd-nspawn container or VM registered with systemd-machined, or an image that can be started.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Machine {
+    pub name: String,
+    pub class: String, // "container" or "vm"; empty for images that aren't running
+    pub os: String,    // empty when machined doesn't know it
+    pub running: bool,
+}
+
+/// How much of its journal one unit accounts for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalUsage {
+    pub unit: String,
+    pub unit_entries: u64, // since boot, like total_entries
+    pub total_entries: u64,
+    pub disk_usage: Option<String>, // as reported by `journalctl --disk-usage`, e.g. "96.0M"
+}
+
+/// A unit that entered the failed state at some point since boot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BootFailure {
+    pub unit: String,
+    pub time: String,   // time of the most recent failure
+    pub result: String, // e.g., "exit-code", "timeout", "oom-kill"
+    pub count: usize,
+    pub last_line: String, // the unit's last error, or its last line if it logged no errors
 }
@@ -0,0 +1,569 @@
+// Records sessions to a JSON Lines file and replays them without touching systemd.
+
+use super::config::{self, Config};
+use super::model::{
+    BootFailure, JournalUsage, LogChunk, Machine, Relation, Service, SymlinkChange,
+};
+use super::systemd::{
+    JournalUnavailable, LogRange, MachineAction, ServiceAction, SystemdClient, UnitFileAction,
+};
+use anyhow::{Context, Result};
+use crossterm::event::{KeyCode, KeyEvent};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::mpsc::Receiver;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+enum Entry {
+    /// The config file the session ran with, so presets and columns match on replay.
+    Config {
+        contents: String,
+    },
+    Key {
+        key: String,
+    },
+    /// One call to the backend and what it returned.
+    Call {
+        method: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        args: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ok: Option<Value>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+        /// The error was a JournalUnavailable, which the app handles differently.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        journal_unavailable: bool,
+    },
+}
+
+/// Appends entries to a recording. Clones share the file, so the app and client can both write.
+#[derive(Clone)]
+pub struct Recorder {
+    file: Rc<RefCell<File>>,
+}
+
+impl Recorder {
+    pub fn create(path: &Path, config_contents: &str) -> Result<Self> {
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create recording {}", path.display()))?;
+        let recorder = Self {
+            file: Rc::new(RefCell::new(file)),
+        };
+        recorder.write(&Entry::Config {
+            contents: config_contents.to_string(),
+        });
+        Ok(recorder)
+    }
+
+    /// Recording is best effort; a full disk shouldn't take the monitor down with it.
+    fn write(&self, entry: &Entry) {
+        if let Ok(line) = serde_json::to_string(entry) {
+            let _ = writeln!(self.file.borrow_mut(), "{}", line);
+        }
+    }
+
+    pub fn key(&self, code: KeyCode) {
+        if let Some(key) = encode_key(code) {
+            self.write(&Entry::Key { key });
+        }
+    }
+
+    fn call<T: Serialize>(&self, method: &str, args: &[&str], result: &Result<T>) {
+        let (ok, error, journal_unavailable) = match result {
+            Ok(value) => (serde_json::to_value(value).ok(), None, false),
+            Err(err) => match err.downcast_ref::<JournalUnavailable>() {
+                Some(unavailable) => (None, Some(unavailable.0.clone()), true),
+                None => (None, Some(format!("{:#}", err)), false),
+            },
+        };
+        self.write(&Entry::Call {
+            method: method.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            ok,
+            error,
+            journal_unavailable,
+        });
+    }
+}
+
+/// Passes every call through to the real backend and records what came back.
+pub struct RecordingClient {
+    inner: Box<dyn SystemdClient>,
+    recorder: Recorder,
+}
+
+impl RecordingClient {
+    pub fn new(inner: Box<dyn SystemdClient>, recorder: Recorder) -> Self {
+        Self { inner, recorder }
+    }
+}
+
+impl SystemdClient for RecordingClient {
+    fn get_user_services(&self, runtime: bool) -> Result<Vec<Service>> {
+        let result = self.inner.get_user_services(runtime);
+        self.recorder
+            .call("get_user_services", &[&runtime.to_string()], &result);
+        result
+    }
+
+    fn control_service(&self, service_name: &str, action: ServiceAction) -> Result<()> {
+        let result = self.inner.control_service(service_name, action);
+        let action = format!("{:?}", action);
+        self.recorder
+            .call("control_service", &[service_name, &action], &result);
+        result
+    }
+
+    fn preview_unit_files(
+        &self,
+        units: &[String],
+        action: UnitFileAction,
+    ) -> Result<Vec<SymlinkChange>> {
+        let result = self.inner.preview_unit_files(units, action);
+        self.recorder.call(
+            "preview_unit_files",
+            &[action.verb(), &units.join(" ")],
+            &result,
+        );
+        result
+    }
+
+    fn change_unit_files(&self, units: &[String], action: UnitFileAction) -> Result<()> {
+        let result = self.inner.change_unit_files(units, action);
+        self.recorder.call(
+            "change_unit_files",
+            &[action.verb(), &units.join(" ")],
+            &result,
+        );
+        result
+    }
+
+    fn get_service_logs(&self, service_name: &str, range: &LogRange) -> Result<LogChunk> {
+        let result = self.inner.get_service_logs(service_name, range);
+        let range = format!("{:?}", range);
+        self.recorder
+            .call("get_service_logs", &[service_name, &range], &result);
+        result
+    }
+
+    fn get_boot_failures(&self) -> Result<Vec<BootFailure>> {
+        let result = self.inner.get_boot_failures();
+        self.recorder.call("get_boot_failures", &[], &result);
+        result
+    }
+
+    fn get_journal_usage(&self, unit: &str) -> Result<JournalUsage> {
+        let result = self.inner.get_journal_usage(unit);
+        self.recorder.call("get_journal_usage", &[unit], &result);
+        result
+    }
+
+    fn get_machines(&self) -> Result<Vec<Machine>> {
+        let result = self.inner.get_machines();
+        self.recorder.call("get_machines", &[], &result);
+        result
+    }
+
+    fn control_machine(&self, name: &str, action: MachineAction) -> Result<()> {
+        let result = self.inner.control_machine(name, action);
+        self.recorder
+            .call("control_machine", &[name, action.verb()], &result);
+        result
+    }
+
+    fn get_machine_logs(&self, name: &str, range: &LogRange) -> Result<LogChunk> {
+        let result = self.inner.get_machine_logs(name, range);
+        let range = format!("{:?}", range);
+        self.recorder
+            .call("get_machine_logs", &[name, &range], &result);
+        result
+    }
+
+    fn get_dependencies(&self, unit: &str) -> Result<Vec<Relation>> {
+        let result = self.inner.get_dependencies(unit);
+        self.recorder.call("get_dependencies", &[unit], &result);
+        result
+    }
+
+    fn get_documentation(&self, unit: &str) -> Result<Vec<String>> {
+        let result = self.inner.get_documentation(unit);
+        self.recorder.call("get_documentation", &[unit], &result);
+        result
+    }
+
+    fn get_unit(&self, unit: &str) -> Result<Service> {
+        let result = self.inner.get_unit(unit);
+        self.recorder.call("get_unit", &[unit], &result);
+        result
+    }
+
+    /// Not recorded, so a replay never opens anything.
+    fn should_open_documentation(&self, uri: &str) -> bool {
+        self.inner.should_open_documentation(uri)
+    }
+
+    fn notify(&self, summary: &str, body: &str) {
+        self.inner.notify(summary, body);
+    }
+
+    fn watch_units(&self) -> Option<Receiver<()>> {
+        self.inner.watch_units()
+    }
+}
+
+/// A recording in the temp dir, removed when dropped so a failing test doesn't leave it behind.
+#[cfg(test)]
+pub struct TempRecording(pub std::path::PathBuf);
+
+#[cfg(test)]
+impl TempRecording {
+    pub fn new(name: &str) -> Self {
+        Self(std::env::temp_dir().join(format!(
+            "systemd-tui-monitor-{}-{}.jsonl",
+            name,
+            std::process::id()
+        )))
+    }
+}
+
+#[cfg(test)]
+impl Drop for TempRecording {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// What the replay should do next.
+pub enum Step {
+    Key(KeyEvent),
+    /// A call the app made on its own (a timed refresh, say) rather than in response to a key.
+    Call(String),
+}
+
+/// A recording being played back. Clones share the position, so the client and the
+/// app driving it stay in step.
+#[derive(Clone)]
+pub struct Replay {
+    entries: Rc<RefCell<VecDeque<Entry>>>,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read recording {}", path.display()))?;
+        Self::parse(&contents).with_context(|| format!("Invalid recording {}", path.display()))
+    }
+
+    fn parse(contents: &str) -> Result<Self> {
+        let entries = contents
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                serde_json::from_str(line).with_context(|| format!("Line {} is not valid", i + 1))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            entries: Rc::new(RefCell::new(entries)),
+        })
+    }
+
+    /// The config the session was recorded with. It is never saved back anywhere.
+    pub fn config(&self) -> Result<Config> {
+        let mut entries = self.entries.borrow_mut();
+        match entries.front() {
+            Some(Entry::Config { contents }) => {
+                let config = config::parse(contents).context("Recorded config is invalid")?;
+                entries.pop_front();
+                Ok(config)
+            }
+            _ => Ok(Config::default()),
+        }
+    }
+
+    pub fn client(&self) -> ReplayClient {
+        ReplayClient {
+            replay: self.clone(),
+        }
+    }
+
+    /// Takes the next key, or names the next call without consuming it so the app can make it.
+    pub fn next_step(&self) -> Option<Step> {
+        let mut entries = self.entries.borrow_mut();
+        loop {
+            match entries.front()? {
+                Entry::Config { .. } => {
+                    entries.pop_front();
+                }
+                Entry::Key { key } => {
+                    let code = decode_key(key);
+                    entries.pop_front();
+                    // Keys from a newer version are skipped rather than guessed at.
+                    if let Some(code) = code {
+                        return Some(Step::Key(KeyEvent::from(code)));
+                    }
+                }
+                Entry::Call { method, .. } => return Some(Step::Call(method.clone())),
+            }
+        }
+    }
+
+    pub fn next_is_key(&self) -> bool {
+        matches!(self.entries.borrow().front(), Some(Entry::Key { .. }))
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.entries.borrow().len()
+    }
+
+    /// Drops the next entry, for calls the app didn't make this time around.
+    pub fn skip(&self) {
+        self.entries.borrow_mut().pop_front();
+    }
+
+    fn answer<T: DeserializeOwned>(&self, method: &str) -> Result<T> {
+        let mut entries = self.entries.borrow_mut();
+        match entries.pop_front() {
+            Some(Entry::Call {
+                method: recorded,
+                ok,
+                error,
+                journal_unavailable,
+                ..
+            }) if recorded == method => match error {
+                Some(error) if journal_unavailable => Err(JournalUnavailable(error).into()),
+                Some(error) => Err(anyhow::anyhow!("{}", error)),
+                None => serde_json::from_value(ok.unwrap_or(Value::Null))
+                    .with_context(|| format!("Recorded {} result is invalid", method)),
+            },
+            other => {
+                let found = match &other {
+                    Some(Entry::Call { method, .. }) => format!("a {} call", method),
+                    Some(Entry::Key { key }) => format!("the key {}", key),
+                    Some(Entry::Config { .. }) => "a config".to_string(),
+                    None => "its end".to_string(),
+                };
+                if let Some(entry) = other {
+                    entries.push_front(entry);
+                }
+                Err(anyhow::anyhow!(
+                    "Replay out of sync: the app called {} but the recording is at {}",
+                    method,
+                    found
+                ))
+            }
+        }
+    }
+}
+
+/// Answers every call from the recording, in order, without running anything.
+pub struct ReplayClient {
+    replay: Replay,
+}
+
+impl SystemdClient for ReplayClient {
+    fn get_user_services(&self, _runtime: bool) -> Result<Vec<Service>> {
+        self.replay.answer("get_user_services")
+    }
+
+    fn control_service(&self, _service_name: &str, _action: ServiceAction) -> Result<()> {
+        self.replay.answer("control_service")
+    }
+
+    fn preview_unit_files(
+        &self,
+        _units: &[String],
+        _action: UnitFileAction,
+    ) -> Result<Vec<SymlinkChange>> {
+        self.replay.answer("preview_unit_files")
+    }
+
+    fn change_unit_files(&self, _units: &[String], _action: UnitFileAction) -> Result<()> {
+        self.replay.answer("change_unit_files")
+    }
+
+    fn get_service_logs(&self, _service_name: &str, _range: &LogRange) -> Result<LogChunk> {
+        self.replay.answer("get_service_logs")
+    }
+
+    fn get_boot_failures(&self) -> Result<Vec<BootFailure>> {
+        self.replay.answer("get_boot_failures")
+    }
+
+    fn get_journal_usage(&self, _unit: &str) -> Result<JournalUsage> {
+        self.replay.answer("get_journal_usage")
+    }
+
+    fn get_machines(&self) -> Result<Vec<Machine>> {
+        self.replay.answer("get_machines")
+    }
+
+    fn control_machine(&self, _name: &str, _action: MachineAction) -> Result<()> {
+        self.replay.answer("control_machine")
+    }
+
+    fn get_machine_logs(&self, _name: &str, _range: &LogRange) -> Result<LogChunk> {
+        self.replay.answer("get_machine_logs")
+    }
+
+    fn get_dependencies(&self, _unit: &str) -> Result<Vec<Relation>> {
+        self.replay.answer("get_dependencies")
+    }
+
+    fn get_documentation(&self, _unit: &str) -> Result<Vec<String>> {
+        self.replay.answer("get_documentation")
+    }
+
+    fn get_unit(&self, _unit: &str) -> Result<Service> {
+        self.replay.answer("get_unit")
+    }
+
+    fn should_open_documentation(&self, _uri: &str) -> bool {
+        false
+    }
+
+    fn notify(&self, _summary: &str, _body: &str) {}
+
+    fn watch_units(&self) -> Option<Receiver<()>> {
+        None
+    }
+}
+
+fn encode_key(code: KeyCode) -> Option<String> {
+    let key = match code {
+        KeyCode::Char(c) => return Some(c.to_string()),
+        KeyCode::Esc => "<esc>",
+        KeyCode::Enter => "<enter>",
+        KeyCode::Tab => "<tab>",
+        KeyCode::Backspace => "<backspace>",
+        KeyCode::Up => "<up>",
+        KeyCode::Down => "<down>",
+        KeyCode::End => "<end>",
+        // The app ignores everything else.
+        _ => return None,
+    };
+    Some(key.to_string())
+}
+
+fn decode_key(key: &str) -> Option<KeyCode> {
+    let code = match key {
+        "<esc>" => KeyCode::Esc,
+        "<enter>" => KeyCode::Enter,
+        "<tab>" => KeyCode::Tab,
+        "<backspace>" => KeyCode::Backspace,
+        "<up>" => KeyCode::Up,
+        "<down>" => KeyCode::Down,
+        "<end>" => KeyCode::End,
+        _ => {
+            let mut chars = key.chars();
+            let c = chars.next()?;
+            return chars.next().is_none().then_some(KeyCode::Char(c));
+        }
+    };
+    Some(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::systemd::mock::{MockClient, service};
+
+    #[test]
+    fn keys_round_trip() {
+        for code in [
+            KeyCode::Char('j'),
+            KeyCode::Char('<'),
+            KeyCode::Char(' '),
+            KeyCode::Esc,
+            KeyCode::Enter,
+            KeyCode::Tab,
+        ] {
+            assert_eq!(decode_key(&encode_key(code).unwrap()), Some(code));
+        }
+        assert_eq!(encode_key(KeyCode::F(1)), None);
+        assert_eq!(decode_key("<f1>"), None);
+    }
+
+    #[test]
+    fn replay_returns_recorded_results_in_order() {
+        let file = TempRecording::new("recording");
+        let path = &file.0;
+
+        let mock = MockClient::new(vec![service("mpd.service", "active", "running", true)]);
+        mock.set_logs_error(Some("journalctl not found"));
+        let recorder = Recorder::create(path, "columns = [\"memory\"]\n").unwrap();
+        let client = RecordingClient::new(Box::new(mock.clone()), recorder.clone());
+
+        client.get_user_services(false).unwrap();
+        recorder.key(KeyCode::Char('l'));
+        client
+            .get_service_logs("mpd.service", &LogRange::Last(100))
+            .unwrap_err();
+        client
+            .control_service("mpd.service", ServiceAction::Restart)
+            .unwrap();
+        drop((client, recorder));
+
+        let replay = Replay::load(path).unwrap();
+
+        assert_eq!(replay.config().unwrap().columns, [config::Column::Memory]);
+        let client = replay.client();
+        assert!(
+            matches!(replay.next_step(), Some(Step::Call(method)) if method == "get_user_services")
+        );
+        assert_eq!(
+            client.get_user_services(false).unwrap()[0].name,
+            "mpd.service"
+        );
+
+        assert!(
+            matches!(replay.next_step(), Some(Step::Key(key)) if key.code == KeyCode::Char('l'))
+        );
+        let err = client
+            .get_service_logs("mpd.service", &LogRange::Last(100))
+            .unwrap_err();
+        assert!(err.downcast_ref::<JournalUnavailable>().is_some());
+
+        // Calls that don't match the recording are reported, not guessed.
+        let err = client.get_boot_failures().unwrap_err();
+        assert!(err.to_string().contains("control_service"));
+        client
+            .control_service("mpd.service", ServiceAction::Restart)
+            .unwrap();
+        assert!(replay.next_step().is_none());
+    }
+
+    #[test]
+    fn following_logs_records_only_new_lines() {
+        let file = TempRecording::new("following");
+        let mock = MockClient::new(Vec::new());
+        mock.set_logs((0..100).map(|i| format!("line {i}")).collect());
+        let recorder = Recorder::create(&file.0, "").unwrap();
+        let client = RecordingClient::new(Box::new(mock.clone()), recorder);
+
+        let first = client
+            .get_service_logs("mpd.service", &LogRange::Last(100))
+            .unwrap();
+        mock.set_logs((0..101).map(|i| format!("line {i}")).collect());
+        client
+            .get_service_logs("mpd.service", &LogRange::After(first.cursor.unwrap()))
+            .unwrap();
+
+        let contents = fs::read_to_string(&file.0).unwrap();
+        let last: Entry = serde_json::from_str(contents.lines().last().unwrap()).unwrap();
+        let Entry::Call { ok: Some(ok), .. } = last else {
+            panic!("expected a call, got {:?}", last);
+        };
+        let chunk: LogChunk = serde_json::from_value(ok).unwrap();
+        assert_eq!(chunk.lines, ["line 100"]);
+    }
+}
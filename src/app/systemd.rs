@@ -1,27 +1,244 @@
 // Handles all interactions with the `systemctl` command.
 
-use super::model::Service;
+use super::model::{
+    BootFailure, JournalUsage, LogChunk, Machine, Relation, Service, SymlinkChange,
+};
+use super::watcher;
 use anyhow::{Context, Result};
-use std::collections::HashSet;
-use std::path::PathBuf;
-use std::process::Command;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::Receiver;
 use std::{env, fs};
 
+#[cfg(test)]
+pub mod mock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ServiceAction {
     Start,
     Stop,
     Restart,
 }
 
+/// Which part of a journal to read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogRange {
+    /// The newest n lines.
+    Last(usize),
+    /// Everything after a cursor returned by an earlier read.
+    After(String),
+    /// Up to n entries before a cursor, oldest first; the chunk's cursor is then the oldest.
+    Before(String, usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnitFileAction {
+    Enable,
+    Disable,
+    Mask,
+}
+
+impl UnitFileAction {
+    pub fn verb(self) -> &'static str {
+        match self {
+            UnitFileAction::Enable => "enable",
+            UnitFileAction::Disable => "disable",
+            UnitFileAction::Mask => "mask",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            UnitFileAction::Enable => "Enable",
+            UnitFileAction::Disable => "Disable",
+            UnitFileAction::Mask => "Mask",
+        }
+    }
+
+    pub fn past_tense(self) -> &'static str {
+        match self {
+            UnitFileAction::Enable => "Enabled",
+            UnitFileAction::Disable => "Disabled",
+            UnitFileAction::Mask => "Masked",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MachineAction {
+    Start,
+    PowerOff,
+    Terminate,
+}
+
+impl MachineAction {
+    pub fn verb(self) -> &'static str {
+        match self {
+            MachineAction::Start => "start",
+            MachineAction::PowerOff => "poweroff",
+            MachineAction::Terminate => "terminate",
+        }
+    }
+}
+
+/// Logs can't be read from the journal at all, as opposed to a one-off failure.
+#[derive(Debug)]
+pub struct JournalUnavailable(pub String);
+
+impl fmt::Display for JournalUnavailable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Logs unavailable: {}", self.0)
+    }
+}
+
+impl std::error::Error for JournalUnavailable {}
+
+/// Dependency properties shown in the relationship view, in display order.
+const RELATION_PROPERTIES: [&str; 10] = [
+    "Requires",
+    "Wants",
+    "BindsTo",
+    "PartOf",
+    "After",
+    "Before",
+    "TriggeredBy",
+    "Triggers",
+    "RequiredBy",
+    "WantedBy",
+];
+
+/// Everything the app needs from systemd, so the backend can be swapped out.
+pub trait SystemdClient {
+    /// With `runtime`, memory and uptime are filled in too, at the cost of another systemctl call.
+    fn get_user_services(&self, runtime: bool) -> Result<Vec<Service>>;
+    fn control_service(&self, service_name: &str, action: ServiceAction) -> Result<()>;
+    /// The symlinks `change_unit_files` would create or remove, so they can be confirmed first.
+    fn preview_unit_files(
+        &self,
+        units: &[String],
+        action: UnitFileAction,
+    ) -> Result<Vec<SymlinkChange>>;
+    fn change_unit_files(&self, units: &[String], action: UnitFileAction) -> Result<()>;
+    /// The unit's output in `range`, with a cursor to fetch only newer lines next time.
+    fn get_service_logs(&self, service_name: &str, range: &LogRange) -> Result<LogChunk>;
+    fn get_boot_failures(&self) -> Result<Vec<BootFailure>>;
+    fn get_journal_usage(&self, unit: &str) -> Result<JournalUsage>;
+    /// Running machines plus the images machined could start.
+    fn get_machines(&self) -> Result<Vec<Machine>>;
+    fn control_machine(&self, name: &str, action: MachineAction) -> Result<()>;
+    fn get_machine_logs(&self, name: &str, range: &LogRange) -> Result<LogChunk>;
+    fn get_dependencies(&self, unit: &str) -> Result<Vec<Relation>>;
+    /// The URIs in the unit's Documentation=, e.g. "man:mpd(1)".
+    fn get_documentation(&self, unit: &str) -> Result<Vec<String>>;
+    /// Looks up a single unit, including ones outside the service list (sockets, timers, ...).
+    fn get_unit(&self, unit: &str) -> Result<Service>;
+    /// Whether a Documentation= URI should really be opened; replays and tests say no. The app
+    /// does the opening itself, since man needs the terminal.
+    fn should_open_documentation(&self, uri: &str) -> bool;
+    /// Shows a desktop notification; failures are ignored since the status bar shows it too.
+    fn notify(&self, summary: &str, body: &str);
+    /// A channel that ticks whenever units change, if the backend can push updates.
+    fn watch_units(&self) -> Option<Receiver<()>>;
+}
+
+/// Talks to the user manager by shelling out to `systemctl` and `journalctl`.
+pub struct SystemctlClient;
+
+impl SystemdClient for SystemctlClient {
+    fn get_user_services(&self, runtime: bool) -> Result<Vec<Service>> {
+        get_user_services(runtime)
+    }
+
+    fn control_service(&self, service_name: &str, action: ServiceAction) -> Result<()> {
+        control_service(service_name, action)
+    }
+
+    fn preview_unit_files(
+        &self,
+        units: &[String],
+        action: UnitFileAction,
+    ) -> Result<Vec<SymlinkChange>> {
+        preview_unit_files(units, action)
+    }
+
+    fn change_unit_files(&self, units: &[String], action: UnitFileAction) -> Result<()> {
+        change_unit_files(units, action)
+    }
+
+    fn get_service_logs(&self, service_name: &str, range: &LogRange) -> Result<LogChunk> {
+        get_service_logs(service_name, range)
+    }
+
+    fn get_boot_failures(&self) -> Result<Vec<BootFailure>> {
+        get_boot_failures()
+    }
+
+    fn get_journal_usage(&self, unit: &str) -> Result<JournalUsage> {
+        get_journal_usage(unit)
+    }
+
+    fn get_machines(&self) -> Result<Vec<Machine>> {
+        get_machines()
+    }
+
+    fn control_machine(&self, name: &str, action: MachineAction) -> Result<()> {
+        control_machine(name, action)
+    }
+
+    fn get_machine_logs(&self, name: &str, range: &LogRange) -> Result<LogChunk> {
+        get_machine_logs(name, range)
+    }
+
+    fn get_dependencies(&self, unit: &str) -> Result<Vec<Relation>> {
+        get_dependencies(unit)
+    }
+
+    fn get_documentation(&self, unit: &str) -> Result<Vec<String>> {
+        get_documentation(unit)
+    }
+
+    fn get_unit(&self, unit: &str) -> Result<Service> {
+        get_unit(unit)
+    }
+
+    fn should_open_documentation(&self, _uri: &str) -> bool {
+        true
+    }
+
+    fn notify(&self, summary: &str, body: &str) {
+        // A slow notification daemon mustn't hold up the refresh, so it's reaped on its own thread.
+        let child = Command::new("notify-send")
+            .arg("--app-name=systemd-tui-monitor")
+            .arg(summary)
+            .arg(body)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        if let Ok(mut child) = child {
+            std::thread::spawn(move || child.wait());
+        }
+    }
+
+    fn watch_units(&self) -> Option<Receiver<()>> {
+        watcher::spawn_unit_watcher()
+    }
+}
+
+/// Where user units (and the symlinks enabling them) live.
+fn user_unit_dir() -> Option<PathBuf> {
+    env::var_os("HOME").map(|home| PathBuf::from(home).join(".config/systemd/user"))
+}
+
 fn get_user_defined_services() -> HashSet<String> {
     let mut names = HashSet::new();
-    if let Ok(home) = env::var("HOME") {
-        let config_path = PathBuf::from(home).join(".config/systemd/user");
-
+    if let Some(config_path) = user_unit_dir() {
         if let Ok(entries) = fs::read_dir(config_path) {
             for entry in entries.flatten() {
                 if let Ok(file_name) = entry.file_name().into_string() {
-
                     if file_name.ends_with(".service") {
                         names.insert(file_name);
                     }
@@ -32,10 +249,9 @@ fn get_user_defined_services() -> HashSet<String> {
     names
 }
 
-pub fn get_user_services() -> Result<Vec<Service>> {
+fn get_user_services(runtime: bool) -> Result<Vec<Service>> {
     let user_config_services = get_user_defined_services();
 
-
     // but sticking to your text parsing for simplicity, added --plain to ensure no colors/styling
     let output = Command::new("systemctl")
         .arg("--user")
@@ -49,16 +265,118 @@ pub fn get_user_services() -> Result<Vec<Service>> {
         .context("Failed to execute systemctl command")?;
 
     if !output.status.success() {
-        return Err(anyhow::anyhow!("systemctl returned non-zero status"));
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow::anyhow!(
+            "systemctl returned non-zero status: {}",
+            stderr.trim()
+        ));
+    }
+
+    // systemctl list-unit-files is VERY slow compared to list-units.
+    // If you experience lag, consider removing this second command and only
+    // showing loaded units. For now, I've left it but ensure it's plain text.
+    let output_files = Command::new("systemctl")
+        .arg("--user")
+        .arg("list-unit-files")
+        .arg("--type=service")
+        .arg("--no-pager")
+        .arg("--no-legend")
+        .arg("--plain")
+        .output()
+        .context("Failed to execute systemctl list-unit-files")?;
+
+    let unit_files = if output_files.status.success() {
+        String::from_utf8_lossy(&output_files.stdout).into_owned()
+    } else {
+        String::new()
+    };
+
+    let mut services = parse_services(
+        &String::from_utf8_lossy(&output.stdout),
+        &unit_files,
+        &user_config_services,
+    );
+
+    // Memory and uptime only mean something for running units, which keeps this call small.
+    let active: Vec<&str> = services
+        .iter()
+        .filter(|s| s.active_state == "active")
+        .map(|s| s.name.as_str())
+        .collect();
+
+    if runtime && !active.is_empty() {
+        let output_show = Command::new("systemctl")
+            .arg("--user")
+            .arg("show")
+            .arg("--timestamp=unix")
+            .arg("--property=Id,MemoryCurrent,ActiveEnterTimestamp")
+            .arg("--")
+            .args(&active)
+            .output();
+
+        // Older systemd doesn't know --timestamp=unix; then, as when systemctl can't be run
+        // at all, the columns just stay empty.
+        if let Ok(output_show) = output_show {
+            if output_show.status.success() {
+                apply_runtime_properties(
+                    &mut services,
+                    &String::from_utf8_lossy(&output_show.stdout),
+                );
+            }
+        }
+    }
+
+    Ok(services)
+}
+
+/// Combines `list-units` and `list-unit-files` output into one sorted list.
+fn parse_services(
+    list_units: &str,
+    list_unit_files: &str,
+    user_config_services: &HashSet<String>,
+) -> Vec<Service> {
+    let mut services = parse_list_units(list_units, user_config_services);
+    let seen_names: HashSet<String> = services.iter().map(|s| s.name.clone()).collect();
+    let unit_files: Vec<(&str, &str)> = parse_list_unit_files(list_unit_files).collect();
+
+    let enablement: HashMap<&str, &str> = unit_files.iter().copied().collect();
+    for service in &mut services {
+        if let Some(state) = enablement.get(service.name.as_str()) {
+            service.enablement = state.to_string();
+        }
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
+    for (name, state) in unit_files {
+        if !seen_names.contains(name) {
+            let is_config = user_config_services.contains(name);
+
+            // if you really want to see every installed service on the OS.
+            // Current logic shows EVERYTHING installed on the OS.
+            services.push(Service {
+                name: name.to_string(),
+                loaded_state: "unloaded".to_string(),
+                active_state: "inactive".to_string(),
+                sub_state: "dead".to_string(),
+                description: String::new(),
+                enablement: state.to_string(),
+                memory_bytes: None,
+                active_since: None,
+                is_user_config: is_config,
+            });
+        }
+    }
+
+    services.sort_by(|a, b| a.name.cmp(&b.name));
+    services
+}
+
+/// Parses `UNIT LOAD ACTIVE SUB DESCRIPTION...` rows.
+fn parse_list_units(stdout: &str, user_config_services: &HashSet<String>) -> Vec<Service> {
     let mut services = Vec::new();
-    let mut seen_names = HashSet::new();
 
     for line in stdout.lines() {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() < 4 {
+        let parts: Vec<&str> = unit_columns(line).collect();
+        if parts.len() < 4 || !is_unit_name(parts[0]) {
             continue;
         }
 
@@ -66,62 +384,79 @@ pub fn get_user_services() -> Result<Vec<Service>> {
         let is_config = user_config_services.contains(&name);
 
         services.push(Service {
-            name: name.clone(),
+            name,
             loaded_state: parts[1].to_string(),
             active_state: parts[2].to_string(),
             sub_state: parts[3].to_string(),
+            description: parts[4..].join(" "),
+            enablement: String::new(),
+            memory_bytes: None,
+            active_since: None,
             is_user_config: is_config,
         });
-
-        seen_names.insert(name);
     }
 
+    services
+}
 
-    // systemctl list-unit-files is VERY slow compared to list-units.
-    // If you experience lag, consider removing this second command and only
-    // showing loaded units. For now, I've left it but ensure it's plain text.
-    let output_files = Command::new("systemctl")
-        .arg("--user")
-        .arg("list-unit-files")
-        .arg("--type=service")
-        .arg("--no-pager")
-        .arg("--no-legend")
-        .arg("--plain")
-        .output()
-        .context("Failed to execute systemctl list-unit-files")?;
-
-    if output_files.status.success() {
-        let stdout_files = String::from_utf8_lossy(&output_files.stdout);
-        for line in stdout_files.lines() {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.is_empty() {
-                continue;
-            }
+/// Parses `UNIT FILE STATE [PRESET]` rows into (name, state) pairs.
+fn parse_list_unit_files(stdout: &str) -> impl Iterator<Item = (&str, &str)> {
+    stdout.lines().filter_map(|line| {
+        let mut parts = unit_columns(line);
+        let name = parts.next().filter(|name| is_unit_name(name))?;
+        Some((name, parts.next().unwrap_or_default()))
+    })
+}
 
-            let name = parts[0];
-            if !seen_names.contains(name) {
-                let is_config = user_config_services.contains(name);
+/// Splits `systemctl show` output for several units into one map per unit.
+fn parse_show_blocks(stdout: &str) -> Vec<HashMap<&str, &str>> {
+    stdout
+        .split("\n\n")
+        .map(|block| {
+            block
+                .lines()
+                .filter_map(|line| line.split_once('='))
+                .collect::<HashMap<_, _>>()
+        })
+        .filter(|properties| !properties.is_empty())
+        .collect()
+}
 
+/// Fills in memory and start time from `systemctl show --timestamp=unix` output.
+fn apply_runtime_properties(services: &mut [Service], stdout: &str) {
+    for properties in parse_show_blocks(stdout) {
+        let Some(service) = properties
+            .get("Id")
+            .and_then(|id| services.iter_mut().find(|s| s.name == *id))
+        else {
+            continue;
+        };
 
-                // if you really want to see every installed service on the OS.
-                // Current logic shows EVERYTHING installed on the OS.
-                services.push(Service {
-                    name: name.to_string(),
-                    loaded_state: "unloaded".to_string(),
-                    active_state: "inactive".to_string(),
-                    sub_state: "dead".to_string(),
-                    is_user_config: is_config,
-                });
-            }
-        }
+        // Unset values come back as "[not set]" or u64::MAX.
+        service.memory_bytes = properties
+            .get("MemoryCurrent")
+            .and_then(|v| v.parse().ok())
+            .filter(|&bytes| bytes != u64::MAX);
+        service.active_since = properties
+            .get("ActiveEnterTimestamp")
+            .and_then(|v| v.strip_prefix('@'))
+            .and_then(|v| v.parse().ok());
     }
+}
 
-    services.sort_by(|a, b| a.name.cmp(&b.name));
+/// Splits a row into columns, dropping the status bullet systemctl prints
+/// in front of failed/not-found units when `--plain` isn't honoured.
+fn unit_columns(line: &str) -> impl Iterator<Item = &str> {
+    line.split_whitespace()
+        .skip_while(|part| matches!(*part, "●" | "*"))
+}
 
-    Ok(services)
+/// Filters out stray legend/summary lines such as "3 unit files listed.".
+fn is_unit_name(name: &str) -> bool {
+    name.ends_with(".service")
 }
 
-pub fn control_service(service_name: &str, action: ServiceAction) -> Result<()> {
+fn control_service(service_name: &str, action: ServiceAction) -> Result<()> {
     let action_str = match action {
         ServiceAction::Start => "start",
         ServiceAction::Stop => "stop",
@@ -142,18 +477,1095 @@ pub fn control_service(service_name: &str, action: ServiceAction) -> Result<()>
     }
 }
 
-pub fn get_service_logs(service_name: &str) -> Result<Vec<String>> {
+fn preview_unit_files(units: &[String], action: UnitFileAction) -> Result<Vec<SymlinkChange>> {
+    let dir = user_unit_dir().context("HOME is not set")?;
+    let mut changes = Vec::new();
+
+    for unit in units {
+        match action {
+            UnitFileAction::Enable => {
+                let output = Command::new("systemctl")
+                    .arg("--user")
+                    .arg("cat")
+                    .arg("--")
+                    .arg(unit)
+                    .output()
+                    .context("Failed to execute systemctl cat")?;
+                if !output.status.success() {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    return Err(anyhow::anyhow!("{}", stderr.trim()));
+                }
+
+                // Links that already exist are left alone by systemctl.
+                changes.extend(
+                    enable_links(&dir, unit, &String::from_utf8_lossy(&output.stdout))
+                        .into_iter()
+                        .filter(|change| match change {
+                            SymlinkChange::Create { link, .. } => {
+                                fs::symlink_metadata(link).is_err()
+                            }
+                            SymlinkChange::Remove { .. } | SymlinkChange::Blocked { .. } => true,
+                        }),
+                );
+            }
+            UnitFileAction::Disable => changes.extend(existing_links(&dir, unit)),
+            UnitFileAction::Mask => {
+                let link = dir.join(unit);
+                // systemctl only masks over a missing file; a real unit file here is refused.
+                match fs::read_link(&link) {
+                    Ok(target) if target == Path::new("/dev/null") => {}
+                    _ if fs::symlink_metadata(&link).is_ok() => {
+                        changes.push(SymlinkChange::Blocked {
+                            link,
+                            reason: "unit file exists, systemctl won't mask over it".to_string(),
+                        })
+                    }
+                    _ => changes.push(SymlinkChange::Create {
+                        link,
+                        target: PathBuf::from("/dev/null"),
+                    }),
+                }
+            }
+        }
+    }
+
+    Ok(changes)
+}
+
+/// The links `systemctl enable` makes from the `[Install]` section in `systemctl cat` output.
+fn enable_links(dir: &Path, unit: &str, unit_cat: &str) -> Vec<SymlinkChange> {
+    // The first comment is the unit file itself, later ones are drop-ins.
+    let Some(fragment) = unit_cat
+        .lines()
+        .find_map(|line| line.strip_prefix("# "))
+        .map(PathBuf::from)
+    else {
+        return Vec::new();
+    };
+
+    let mut in_install = false;
+    let mut links = Vec::new();
+    for line in unit_cat.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_install = line == "[Install]";
+            continue;
+        }
+        if !in_install {
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        for name in value.split_whitespace() {
+            let link = match key.trim() {
+                "WantedBy" => dir.join(format!("{}.wants", name)).join(unit),
+                "RequiredBy" => dir.join(format!("{}.requires", name)).join(unit),
+                "Alias" => dir.join(name),
+                _ => continue,
+            };
+            links.push(SymlinkChange::Create {
+                link,
+                target: fragment.clone(),
+            });
+        }
+    }
+    links
+}
+
+/// Symlinks to `unit` that `systemctl disable` would remove: .wants/.requires entries and aliases.
+fn existing_links(dir: &Path, unit: &str) -> Vec<SymlinkChange> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut links: Vec<PathBuf> = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+
+        if name.ends_with(".wants") || name.ends_with(".requires") {
+            let link = path.join(unit);
+            if fs::symlink_metadata(&link).is_ok() {
+                links.push(link);
+            }
+        } else if name != unit {
+            if let Ok(target) = fs::read_link(&path) {
+                if target.file_name().is_some_and(|target| target == unit) {
+                    links.push(path);
+                }
+            }
+        }
+    }
+
+    links.sort();
+    links
+        .into_iter()
+        .map(|link| SymlinkChange::Remove { link })
+        .collect()
+}
+
+fn change_unit_files(units: &[String], action: UnitFileAction) -> Result<()> {
+    let output = Command::new("systemctl")
+        .arg("--user")
+        .arg(action.verb())
+        .arg("--")
+        .args(units)
+        .output()
+        .context(format!("Failed to {} units", action.verb()))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(anyhow::anyhow!(
+            "systemctl {} failed: {}",
+            action.verb(),
+            stderr.trim()
+        ))
+    }
+}
+
+/// Selects `range` on a journalctl command line, printing the cursor to continue from last.
+fn log_range_args<'a>(command: &'a mut Command, range: &LogRange) -> &'a mut Command {
+    match range {
+        LogRange::Last(lines) => command.arg("-n").arg(lines.to_string()),
+        LogRange::After(cursor) => command.arg(format!("--after-cursor={}", cursor)),
+        // Reversed, "after" the cursor means the entries logged just before it.
+        LogRange::Before(cursor, entries) => command
+            .arg(format!("--after-cursor={}", cursor))
+            .arg("--reverse")
+            .arg("-n")
+            .arg(entries.to_string()),
+    };
+    command.arg("--show-cursor")
+}
+
+/// Parses the output of a read of `range`, putting entries read backwards back in order.
+fn parse_log_range(stdout: &str, range: &LogRange) -> LogChunk {
+    let mut chunk = parse_log_chunk(stdout);
+    if let LogRange::Before(..) = range {
+        let entries = log_entries(chunk.lines);
+        chunk.lines = entries.into_iter().rev().flatten().collect();
+    }
+    chunk
+}
+
+/// Groups log lines into entries; a multi-line message carries on in indented lines.
+fn log_entries(lines: Vec<String>) -> Vec<Vec<String>> {
+    let mut entries: Vec<Vec<String>> = Vec::new();
+    for line in lines {
+        match entries.last_mut() {
+            Some(entry) if line.starts_with(char::is_whitespace) => entry.push(line),
+            _ => entries.push(vec![line]),
+        }
+    }
+    entries
+}
+
+/// How many journal entries the lines hold, leaving out continuations and "-- Boot" markers.
+pub fn count_log_entries(lines: &[String]) -> usize {
+    lines
+        .iter()
+        .filter(|line| !line.starts_with(char::is_whitespace) && !line.starts_with("-- "))
+        .count()
+}
+
+/// Splits the "-- cursor: ..." trailer of --show-cursor output off the log lines.
+fn parse_log_chunk(stdout: &str) -> LogChunk {
+    let mut lines: Vec<String> = stdout
+        .lines()
+        .filter(|line| *line != "-- No entries --")
+        .map(|s| s.to_string())
+        .collect();
+    let cursor = lines
+        .last()
+        .and_then(|line| line.strip_prefix("-- cursor: "))
+        .map(|cursor| cursor.to_string());
+    if cursor.is_some() {
+        lines.pop();
+    }
+    LogChunk { lines, cursor }
+}
+
+fn get_service_logs(service_name: &str, range: &LogRange) -> Result<LogChunk> {
+    let output = log_range_args(
+        Command::new("journalctl")
+            .arg("--user")
+            .arg("-u")
+            .arg(service_name),
+        range,
+    )
+    .arg("--no-pager")
+    .output(); // This blocks!
+
+    // journalctl exits 0 even when it can't open any journal files, so check stderr too.
+    let reason = match output {
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if output.status.success() && !stderr.contains("No journal files were") {
+                return Ok(parse_log_range(
+                    &String::from_utf8_lossy(&output.stdout),
+                    range,
+                ));
+            }
+            match stderr.lines().next() {
+                Some(line) => format!("no journal access ({})", line.trim()),
+                None => "no journal access".to_string(),
+            }
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => "journalctl not found".to_string(),
+        Err(err) => return Err(err).context("Failed to fetch logs"),
+    };
+
+    // Units that log to a file can still be shown without the journal. A cursor means the
+    // journal was readable before, so there's no file to fall back on.
+    match (get_output_file(service_name), range) {
+        (Some(path), LogRange::Last(lines)) => Ok(LogChunk {
+            lines: read_log_file(&path, *lines)?,
+            cursor: None,
+        }),
+        _ => Err(JournalUnavailable(format!(
+            "{} and {} has no StandardOutput=file:",
+            reason, service_name
+        ))
+        .into()),
+    }
+}
+
+/// Runs `systemctl --user show` for one unit, limited to the given properties.
+fn show_unit(unit: &str, properties: &[&str]) -> Result<String> {
+    let output = Command::new("systemctl")
+        .arg("--user")
+        .arg("show")
+        .arg(format!("--property={}", properties.join(",")))
+        .arg("--")
+        .arg(unit)
+        .output()
+        .context("Failed to execute systemctl show")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow::anyhow!(
+            "systemctl show {} failed: {}",
+            unit,
+            stderr.trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn get_dependencies(unit: &str) -> Result<Vec<Relation>> {
+    Ok(parse_relations(&show_unit(unit, &RELATION_PROPERTIES)?))
+}
+
+/// Flattens space-separated dependency lists, ordered by RELATION_PROPERTIES.
+fn parse_relations(stdout: &str) -> Vec<Relation> {
+    let properties = parse_show_blocks(stdout)
+        .into_iter()
+        .next()
+        .unwrap_or_default();
+
+    RELATION_PROPERTIES
+        .iter()
+        .flat_map(|kind| {
+            properties
+                .get(kind)
+                .into_iter()
+                .flat_map(|units| units.split_whitespace())
+                .map(|unit| Relation {
+                    kind: kind.to_string(),
+                    unit: unit.to_string(),
+                })
+        })
+        .collect()
+}
+
+fn get_documentation(unit: &str) -> Result<Vec<String>> {
+    Ok(parse_documentation(&show_unit(unit, &["Documentation"])?))
+}
+
+fn parse_documentation(stdout: &str) -> Vec<String> {
+    stdout
+        .lines()
+        .filter_map(|line| line.strip_prefix("Documentation="))
+        .flat_map(str::split_whitespace)
+        .map(str::to_string)
+        .collect()
+}
+
+fn get_unit(unit: &str) -> Result<Service> {
+    let stdout = show_unit(
+        unit,
+        &[
+            "Id",
+            "LoadState",
+            "ActiveState",
+            "SubState",
+            "Description",
+            "UnitFileState",
+        ],
+    )?;
+    parse_unit(&stdout, &get_user_defined_services())
+        .with_context(|| format!("systemctl show returned nothing for {}", unit))
+}
+
+fn parse_unit(stdout: &str, user_config_services: &HashSet<String>) -> Option<Service> {
+    let properties = parse_show_blocks(stdout).into_iter().next()?;
+    let property = |name: &str| {
+        properties
+            .get(name)
+            .copied()
+            .unwrap_or_default()
+            .to_string()
+    };
+    let name = property("Id");
+    if name.is_empty() {
+        return None;
+    }
+
+    Some(Service {
+        active_state: property("ActiveState"),
+        sub_state: property("SubState"),
+        loaded_state: property("LoadState"),
+        description: property("Description"),
+        enablement: property("UnitFileState"),
+        memory_bytes: None,
+        active_since: None,
+        is_user_config: user_config_services.contains(&name),
+        name,
+    })
+}
+
+fn get_journal_usage(unit: &str) -> Result<JournalUsage> {
+    let disk_usage = Command::new("journalctl")
+        .arg("--user")
+        .arg("--disk-usage")
+        .output()
+        .ok()
+        .and_then(|output| parse_disk_usage(&String::from_utf8_lossy(&output.stdout)));
+
+    Ok(JournalUsage {
+        unit: unit.to_string(),
+        unit_entries: count_journal_entries(Some(unit))?,
+        total_entries: count_journal_entries(None)?,
+        disk_usage,
+    })
+}
+
+/// Counts this boot's entries by streaming one short field per line, without keeping them
+/// around. The whole journal can take seconds to stream, and this runs while the UI waits.
+fn count_journal_entries(unit: Option<&str>) -> Result<u64> {
+    let mut command = Command::new("journalctl");
+    command
+        .arg("--user")
+        .arg("-b")
+        .arg("-o")
+        .arg("cat")
+        .arg("--output-fields=_BOOT_ID")
+        .arg("-q")
+        .arg("--no-pager");
+    if let Some(unit) = unit {
+        command.arg("-u").arg(unit);
+    }
+
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("Failed to execute journalctl")?;
+    let stdout = child.stdout.take().context("journalctl has no stdout")?;
+    let count = count_lines(stdout);
+    child.wait().context("Failed to wait for journalctl")?;
+    Ok(count)
+}
+
+fn count_lines(reader: impl io::Read) -> u64 {
+    BufReader::new(reader).split(b'\n').count() as u64
+}
+
+/// Picks the size out of "Archived and active journals take up 96.0M in the file system."
+fn parse_disk_usage(stdout: &str) -> Option<String> {
+    let (_, rest) = stdout.split_once("take up ")?;
+    rest.split_whitespace().next().map(str::to_string)
+}
+
+/// Runs a `machinectl` listing command, turning a missing binary into a readable error.
+fn machinectl_list(command: &str) -> Result<String> {
+    let output = Command::new("machinectl")
+        .arg(command)
+        .arg("--no-legend")
+        .arg("--no-pager")
+        .output()
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => anyhow::anyhow!("machinectl not found"),
+            _ => anyhow::Error::new(err).context("Failed to execute machinectl"),
+        })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow::anyhow!(
+            "machinectl {} failed: {}",
+            command,
+            stderr.trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn get_machines() -> Result<Vec<Machine>> {
+    Ok(parse_machines(
+        &machinectl_list("list")?,
+        &machinectl_list("list-images")?,
+    ))
+}
+
+/// Combines `machinectl list` (MACHINE CLASS SERVICE OS VERSION ADDRESSES) with
+/// `list-images` (NAME TYPE ...) so stopped images show up as startable.
+fn parse_machines(list: &str, list_images: &str) -> Vec<Machine> {
+    let mut machines: Vec<Machine> = list
+        .lines()
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.len() < 2 {
+                return None;
+            }
+            let os = parts.get(3).filter(|os| **os != "-").unwrap_or(&"");
+            Some(Machine {
+                name: parts[0].to_string(),
+                class: parts[1].to_string(),
+                os: os.to_string(),
+                running: true,
+            })
+        })
+        .collect();
+
+    for name in list_images
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+    {
+        if !machines.iter().any(|m| m.name == name) {
+            machines.push(Machine {
+                name: name.to_string(),
+                class: String::new(),
+                os: String::new(),
+                running: false,
+            });
+        }
+    }
+
+    machines.sort_by(|a, b| a.name.cmp(&b.name));
+    machines
+}
+
+fn control_machine(name: &str, action: MachineAction) -> Result<()> {
+    // Without a polkit agent in the TUI, asking for a password would just hang.
+    let output = Command::new("machinectl")
+        .arg("--no-ask-password")
+        .arg(action.verb())
+        .arg(name)
+        .output()
+        .context(format!("Failed to {} machine {}", action.verb(), name))?;
 
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(anyhow::anyhow!(
+            "machinectl {} {} failed: {}",
+            action.verb(),
+            name,
+            stderr.trim()
+        ))
+    }
+}
+
+fn get_machine_logs(name: &str, range: &LogRange) -> Result<LogChunk> {
+    let output = log_range_args(
+        Command::new("journalctl").arg(format!("--machine={}", name)),
+        range,
+    )
+    .arg("--no-pager")
+    .output()
+    .context("Failed to execute journalctl")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow::anyhow!(
+            "Can't read the journal of {}: {}",
+            name,
+            stderr.trim()
+        ));
+    }
+    Ok(parse_log_range(
+        &String::from_utf8_lossy(&output.stdout),
+        range,
+    ))
+}
+
+// The catalog ID systemd logs with "<unit>: Failed with result '<result>'.".
+const UNIT_FAILED_MESSAGE_ID: &str = "d9b373ed55a64feb8242e02dbe79a49c";
+
+fn get_boot_failures() -> Result<Vec<BootFailure>> {
     let output = Command::new("journalctl")
         .arg("--user")
-        .arg("-u")
-        .arg(service_name)
-        .arg("-n")
-        .arg("100")
+        .arg("-b")
+        .arg("-o")
+        .arg("short-iso")
         .arg("--no-pager")
-        .output() // This blocks!
-        .context("Failed to fetch logs")?;
+        .arg(format!("MESSAGE_ID={}", UNIT_FAILED_MESSAGE_ID))
+        .output()
+        .context("Failed to read failures from the journal")?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() || stderr.contains("No journal files were") {
+        return Err(JournalUnavailable(stderr.trim().to_string()).into());
+    }
+
+    let mut failures = parse_boot_failures(&String::from_utf8_lossy(&output.stdout));
+
+    for failure in &mut failures {
+        failure.last_line = get_last_unit_line(&failure.unit).unwrap_or_default();
+    }
+
+    Ok(failures)
+}
+
+/// The last error the unit itself logged this boot, or its last line of any priority if it
+/// logged none. systemd's own messages about the unit are left out.
+fn get_last_unit_line(unit: &str) -> Option<String> {
+    last_unit_line(unit, Some("err")).or_else(|| last_unit_line(unit, None))
+}
+
+/// The unit's newest line this boot at `priority` or more severe.
+fn last_unit_line(unit: &str, priority: Option<&str>) -> Option<String> {
+    let mut command = Command::new("journalctl");
+    command
+        .arg("--user")
+        .arg("-b")
+        .arg("-n")
+        .arg("1")
+        .arg("-o")
+        .arg("cat")
+        .arg("--no-pager");
+    if let Some(priority) = priority {
+        command.arg("-p").arg(priority);
+    }
+    let output = command
+        .arg(format!("_SYSTEMD_USER_UNIT={}", unit))
+        .output()
+        .ok()?;
+
+    parse_last_line(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// The message in `-o cat` output, skipping markers like "-- No entries --".
+fn parse_last_line(stdout: &str) -> Option<String> {
+    stdout
+        .lines()
+        .find(|line| !line.starts_with("-- "))
+        .map(|line| line.to_string())
+}
+
+/// Groups `short-iso` failure messages by unit, most recent failure first.
+fn parse_boot_failures(stdout: &str) -> Vec<BootFailure> {
+    let mut failures: Vec<BootFailure> = Vec::new();
+
+    for line in stdout.lines().rev() {
+        let Some((time, unit, result)) = parse_failure_line(line) else {
+            continue;
+        };
+
+        match failures.iter_mut().find(|f| f.unit == unit) {
+            Some(failure) => failure.count += 1,
+            None => failures.push(BootFailure {
+                unit: unit.to_string(),
+                time: time.to_string(),
+                result: result.to_string(),
+                count: 1,
+                last_line: String::new(),
+            }),
+        }
+    }
+
+    failures
+}
+
+/// Splits `TIME HOST systemd[PID]: UNIT: Failed with result 'RESULT'.`
+fn parse_failure_line(line: &str) -> Option<(&str, &str, &str)> {
+    let (time, rest) = line.split_once(' ')?;
+    let (_, message) = rest.split_once("]: ")?;
+    let (unit, detail) = message.split_once(": ")?;
+    let result = detail.split('\'').nth(1).unwrap_or(detail);
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    Ok(stdout.lines().map(|s| s.to_string()).collect())
-}
\ No newline at end of file
+    Some((time, unit, result))
+}
+
+/// Finds where a unit sends its stdout (or stderr) if that is a plain file.
+fn get_output_file(service_name: &str) -> Option<PathBuf> {
+    let output = Command::new("systemctl")
+        .arg("--user")
+        .arg("cat")
+        .arg(service_name)
+        .output()
+        .ok()?;
+
+    let path = parse_output_file(&String::from_utf8_lossy(&output.stdout))?;
+    let home = env::var("HOME").unwrap_or_default();
+    Some(PathBuf::from(path.replace("%h", &home)))
+}
+
+/// Picks the effective `StandardOutput=`/`StandardError=` file from `systemctl cat`
+/// output, where later drop-ins override earlier settings.
+fn parse_output_file(unit_cat: &str) -> Option<String> {
+    let mut stdout_file = None;
+    let mut stderr_file = None;
+
+    for line in unit_cat.lines() {
+        let Some((key, value)) = line.trim().split_once('=') else {
+            continue;
+        };
+        let slot = match key.trim() {
+            "StandardOutput" => &mut stdout_file,
+            "StandardError" => &mut stderr_file,
+            _ => continue,
+        };
+        *slot = ["file:", "append:", "truncate:"]
+            .iter()
+            .find_map(|prefix| value.trim().strip_prefix(prefix))
+            .map(|path| path.to_string());
+    }
+
+    stdout_file.or(stderr_file)
+}
+
+fn read_log_file(path: &Path, limit: usize) -> Result<Vec<String>> {
+    let bytes =
+        fs::read(path).with_context(|| format!("Failed to read log file {}", path.display()))?;
+    let contents = String::from_utf8_lossy(&bytes);
+    let lines: Vec<&str> = contents.lines().collect();
+
+    // Match journalctl -n.
+    let start = lines.len().saturating_sub(limit);
+    Ok(lines[start..].iter().map(|s| s.to_string()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIST_UNITS: &str = include_str!("../../tests/fixtures/list-units.txt");
+    const LIST_UNITS_LOCALIZED: &str =
+        include_str!("../../tests/fixtures/list-units-localized.txt");
+    const LIST_UNIT_FILES: &str = include_str!("../../tests/fixtures/list-unit-files.txt");
+
+    fn config(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn find<'a>(services: &'a [Service], name: &str) -> &'a Service {
+        services
+            .iter()
+            .find(|s| s.name == name)
+            .unwrap_or_else(|| panic!("{name} missing"))
+    }
+
+    #[test]
+    fn parses_list_units_columns() {
+        let services = parse_list_units(LIST_UNITS, &config(&["syncthing.service"]));
+        assert_eq!(services.len(), 6);
+
+        let portal = find(&services, "xdg-desktop-portal.service");
+        assert_eq!(portal.loaded_state, "loaded");
+        assert_eq!(portal.active_state, "active");
+        assert_eq!(portal.sub_state, "running");
+        assert!(portal.is_running());
+        assert!(!portal.is_user_config);
+        assert_eq!(portal.description, "Portal service");
+
+        assert!(find(&services, "syncthing.service").is_user_config);
+    }
+
+    #[test]
+    fn keeps_escaped_and_instance_names_intact() {
+        let services = parse_list_units(LIST_UNITS, &HashSet::new());
+
+        let backup = find(&services, "backup\\x2ddaily.service");
+        assert_eq!(backup.sub_state, "dead");
+        // Runs of spaces inside the description collapse.
+        assert_eq!(backup.description, "Nightly backup job");
+        assert!(find(&services, "dbus-:1.2-org.gnome.OnlineAccounts@0.service").is_running());
+    }
+
+    #[test]
+    fn strips_status_bullet_from_failed_and_missing_units() {
+        let services = parse_list_units(LIST_UNITS, &HashSet::new());
+
+        let ghost = find(&services, "ghost.service");
+        assert_eq!(ghost.loaded_state, "not-found");
+        assert_eq!(ghost.active_state, "inactive");
+
+        let syncthing = find(&services, "syncthing.service");
+        assert_eq!(syncthing.active_state, "failed");
+        assert_eq!(syncthing.sub_state, "failed");
+    }
+
+    #[test]
+    fn ignores_localized_legend_and_summary() {
+        let services = parse_list_units(LIST_UNITS_LOCALIZED, &HashSet::new());
+
+        let names: Vec<_> = services.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["pipewire.service", "wireplumber.service"]);
+        assert!(services.iter().all(Service::is_running));
+    }
+
+    #[test]
+    fn unit_files_skip_summary_line() {
+        let files: Vec<_> = parse_list_unit_files(LIST_UNIT_FILES).collect();
+        assert_eq!(files.len(), 6);
+        assert!(!files.iter().any(|(name, _)| name.contains("listed")));
+        assert!(files.contains(&("obex.service", "disabled")));
+    }
+
+    #[test]
+    fn runtime_properties_fill_memory_and_start_time() {
+        let mut services = parse_services(LIST_UNITS, LIST_UNIT_FILES, &HashSet::new());
+        apply_runtime_properties(
+            &mut services,
+            include_str!("../../tests/fixtures/show-runtime.txt"),
+        );
+
+        let portal = find(&services, "xdg-desktop-portal.service");
+        assert_eq!(portal.memory_bytes, Some(5_242_880));
+        assert_eq!(portal.active_since, Some(1_792_130_652));
+
+        // "[not set]" memory and an empty timestamp.
+        let bus = find(&services, "at-spi-dbus-bus.service");
+        assert_eq!(bus.memory_bytes, None);
+        assert_eq!(bus.active_since, None);
+
+        // u64::MAX means memory accounting is off.
+        let accounts = find(&services, "dbus-:1.2-org.gnome.OnlineAccounts@0.service");
+        assert_eq!(accounts.memory_bytes, None);
+        assert_eq!(accounts.active_since, Some(1_792_130_700));
+    }
+
+    #[test]
+    fn pipeline_adds_unloaded_unit_files_and_sorts() {
+        let services = parse_services(
+            LIST_UNITS,
+            LIST_UNIT_FILES,
+            &config(&["backup\\x2ddaily.service", "obex.service"]),
+        );
+
+        // 6 loaded units plus the 3 unit files that aren't loaded.
+        assert_eq!(services.len(), 9);
+        assert!(services.windows(2).all(|w| w[0].name <= w[1].name));
+
+        let obex = find(&services, "obex.service");
+        assert_eq!(obex.loaded_state, "unloaded");
+        assert_eq!(obex.enablement, "disabled");
+        assert_eq!(obex.sub_state, "dead");
+        assert!(obex.is_user_config);
+
+        // Loaded units keep their live state rather than the unit-file placeholder.
+        assert_eq!(find(&services, "syncthing.service").active_state, "failed");
+        assert_eq!(find(&services, "syncthing.service").enablement, "enabled");
+        // Transient units have no unit file.
+        assert_eq!(find(&services, "ghost.service").enablement, "");
+        assert_eq!(
+            services
+                .iter()
+                .filter(|s| s.name == "syncthing.service")
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn empty_output_yields_no_services() {
+        assert!(parse_services("", "", &HashSet::new()).is_empty());
+    }
+
+    #[test]
+    fn output_file_follows_drop_in_overrides() {
+        let unit_cat = "\
+# /home/me/.config/systemd/user/app.service
+[Service]
+ExecStart=/usr/bin/app
+StandardOutput=journal
+StandardError=append:%h/app.err
+
+# /home/me/.config/systemd/user/app.service.d/override.conf
+[Service]
+StandardOutput=file:/tmp/app.log
+";
+        assert_eq!(parse_output_file(unit_cat).as_deref(), Some("/tmp/app.log"));
+
+        let stderr_only = "[Service]\nStandardError=truncate:/var/tmp/app.err\n";
+        assert_eq!(
+            parse_output_file(stderr_only).as_deref(),
+            Some("/var/tmp/app.err")
+        );
+    }
+
+    #[test]
+    fn journal_output_has_no_file() {
+        assert_eq!(
+            parse_output_file("[Service]\nStandardOutput=journal\n"),
+            None
+        );
+        assert_eq!(parse_output_file(""), None);
+    }
+
+    #[test]
+    fn relations_follow_property_order() {
+        let relations = parse_relations(
+            "After=basic.target syncthing.socket\nRequires=syncthing.socket\nTriggeredBy=syncthing.socket\nWants=\n",
+        );
+
+        let pairs: Vec<_> = relations
+            .iter()
+            .map(|r| (r.kind.as_str(), r.unit.as_str()))
+            .collect();
+        assert_eq!(
+            pairs,
+            [
+                ("Requires", "syncthing.socket"),
+                ("After", "basic.target"),
+                ("After", "syncthing.socket"),
+                ("TriggeredBy", "syncthing.socket"),
+            ]
+        );
+    }
+
+    #[test]
+    fn documentation_lists_every_uri() {
+        assert_eq!(
+            parse_documentation("Documentation=man:mpd(1) https://www.musicpd.org/\n"),
+            ["man:mpd(1)", "https://www.musicpd.org/"]
+        );
+        assert!(parse_documentation("Documentation=\n").is_empty());
+    }
+
+    #[test]
+    fn parses_a_single_unit() {
+        let unit = parse_unit(
+            "Id=syncthing.socket\nLoadState=loaded\nActiveState=active\nSubState=listening\nDescription=Syncthing socket\nUnitFileState=enabled\n",
+            &config(&[]),
+        )
+        .unwrap();
+        assert_eq!(unit.name, "syncthing.socket");
+        assert_eq!(unit.sub_state, "listening");
+        assert_eq!(unit.enablement, "enabled");
+        assert!(!unit.is_user_config);
+
+        assert!(parse_unit("", &config(&[])).is_none());
+    }
+
+    #[test]
+    fn enable_links_follow_install_section_and_drop_ins() {
+        let unit_cat = "\
+# /home/me/.config/systemd/user/backup.service
+[Service]
+ExecStart=/usr/bin/backup
+
+[Install]
+WantedBy=default.target
+Alias=bkp.service
+
+# /home/me/.config/systemd/user/backup.service.d/override.conf
+[Install]
+RequiredBy=network-online.target
+";
+        let dir = Path::new("/home/me/.config/systemd/user");
+        let target = PathBuf::from("/home/me/.config/systemd/user/backup.service");
+
+        assert_eq!(
+            enable_links(dir, "backup.service", unit_cat),
+            [
+                SymlinkChange::Create {
+                    link: dir.join("default.target.wants/backup.service"),
+                    target: target.clone(),
+                },
+                SymlinkChange::Create {
+                    link: dir.join("bkp.service"),
+                    target: target.clone(),
+                },
+                SymlinkChange::Create {
+                    link: dir.join("network-online.target.requires/backup.service"),
+                    target,
+                },
+            ]
+        );
+
+        // Static units have nothing to enable.
+        assert!(enable_links(dir, "static.service", "# /x/static.service\n[Service]\n").is_empty());
+    }
+
+    #[test]
+    fn disable_finds_wants_links_and_aliases() {
+        use std::os::unix::fs::symlink;
+
+        let dir = env::temp_dir().join(format!("systemd-tui-monitor-links-{}", std::process::id()));
+        fs::create_dir_all(dir.join("default.target.wants")).unwrap();
+        fs::create_dir_all(dir.join("timers.target.wants")).unwrap();
+        fs::write(dir.join("backup.service"), "[Service]\n").unwrap();
+        symlink(
+            dir.join("backup.service"),
+            dir.join("default.target.wants/backup.service"),
+        )
+        .unwrap();
+        symlink(dir.join("backup.service"), dir.join("bkp.service")).unwrap();
+
+        let links = existing_links(&dir, "backup.service");
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            links,
+            [
+                SymlinkChange::Remove {
+                    link: dir.join("bkp.service"),
+                },
+                SymlinkChange::Remove {
+                    link: dir.join("default.target.wants/backup.service"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn machines_include_stopped_images() {
+        let machines = parse_machines(
+            include_str!("../../tests/fixtures/machinectl-list.txt"),
+            include_str!("../../tests/fixtures/machinectl-list-images.txt"),
+        );
+
+        let names: Vec<_> = machines.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["archlinux", "fedora-dev", "win11"]);
+
+        assert!(!machines[0].running);
+        assert_eq!(machines[1].class, "container");
+        assert_eq!(machines[1].os, "fedora");
+        assert!(machines[1].running);
+        // "-" means machined doesn't know the OS.
+        assert_eq!(machines[2].os, "");
+        assert_eq!(machines[2].class, "vm");
+    }
+
+    #[test]
+    fn parses_journal_disk_usage() {
+        assert_eq!(
+            parse_disk_usage(include_str!(
+                "../../tests/fixtures/journalctl-disk-usage.txt"
+            ))
+            .as_deref(),
+            Some("96.0M")
+        );
+        assert_eq!(parse_disk_usage("No journal files were found.\n"), None);
+    }
+
+    #[test]
+    fn journal_entries_are_counted_one_per_line() {
+        let boot_ids = include_bytes!("../../tests/fixtures/journalctl-boot-ids.txt");
+        assert_eq!(count_lines(&boot_ids[..]), 5);
+        assert_eq!(count_lines(&b""[..]), 0);
+    }
+
+    #[test]
+    fn log_lines_are_split_from_their_cursor() {
+        let chunk = parse_log_chunk(include_str!(
+            "../../tests/fixtures/journalctl-show-cursor.txt"
+        ));
+        assert_eq!(chunk.lines.len(), 4);
+        assert!(
+            chunk.lines[3]
+                .ends_with("Completed initial scan of sendreceive folder \"Music\" (music-x7k2q)")
+        );
+        assert!(
+            chunk
+                .cursor
+                .as_deref()
+                .is_some_and(|cursor| cursor.starts_with("s=9e6f8a1d")
+                    && cursor.ends_with(";x=8c7d6e5f4a3b2c1d"))
+        );
+
+        let empty = parse_log_chunk(include_str!(
+            "../../tests/fixtures/journalctl-no-entries.txt"
+        ));
+        assert_eq!(empty, LogChunk::default());
+    }
+
+    #[test]
+    fn entries_read_backwards_are_put_back_in_order() {
+        let range = LogRange::Before("s=9e6f8a1d".to_string(), 3);
+        let chunk = parse_log_range(
+            include_str!("../../tests/fixtures/journalctl-reverse.txt"),
+            &range,
+        );
+        assert_eq!(chunk.lines.len(), 4);
+        assert!(chunk.lines[0].contains("Loading HTTPS certificate"));
+        assert!(chunk.lines[1].ends_with("Failed to load ignores:"));
+        assert!(
+            chunk.lines[2]
+                .trim_start()
+                .starts_with("open /home/user/Music")
+        );
+        assert!(chunk.lines[3].contains("Ready to synchronize"));
+        assert!(
+            chunk
+                .cursor
+                .is_some_and(|cursor| cursor.ends_with(";x=1a2b3c4d5e6f7081"))
+        );
+        assert_eq!(count_log_entries(&chunk.lines), 3);
+    }
+
+    #[test]
+    fn last_line_skips_journal_markers() {
+        assert_eq!(
+            parse_last_line(include_str!("../../tests/fixtures/journalctl-cat-err.txt")).as_deref(),
+            Some(
+                "[K7Q2M] WARNING: Failed to start listener: listen tcp 0.0.0.0:22000: bind: address already in use"
+            )
+        );
+        assert_eq!(
+            parse_last_line(include_str!(
+                "../../tests/fixtures/journalctl-no-entries.txt"
+            )),
+            None
+        );
+    }
+
+    #[test]
+    fn boot_failures_are_grouped_newest_first() {
+        let failures = parse_boot_failures(include_str!("../../tests/fixtures/boot-failures.txt"));
+
+        let units: Vec<_> = failures.iter().map(|f| f.unit.as_str()).collect();
+        assert_eq!(
+            units,
+            [
+                "app-org.mozilla.firefox@a1b2c3.service",
+                "backup\\x2ddaily.service",
+                "syncthing.service",
+            ]
+        );
+
+        let backup = &failures[1];
+        assert_eq!(backup.count, 2);
+        assert_eq!(backup.time, "2026-10-17T03:04:12+0200");
+        assert_eq!(backup.result, "exit-code");
+
+        assert_eq!(failures[0].result, "oom-kill");
+        assert_eq!(failures[2].result, "timeout");
+    }
+
+    #[test]
+    fn no_failures_when_journal_is_empty() {
+        assert!(
+            parse_boot_failures(include_str!(
+                "../../tests/fixtures/journalctl-no-entries.txt"
+            ))
+            .is_empty()
+        );
+    }
+}
@@ -0,0 +1,342 @@
+// An in-memory stand-in for systemd, used to drive the app in tests.
+
+use super::{
+    JournalUnavailable, LogRange, MachineAction, ServiceAction, SystemdClient, UnitFileAction,
+    parse_services,
+};
+use crate::app::model::{
+    BootFailure, JournalUsage, LogChunk, Machine, Relation, Service, SymlinkChange,
+};
+use anyhow::Result;
+use std::cell::{RefCell, RefMut};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::mpsc::Receiver;
+
+#[derive(Default)]
+struct MockState {
+    services: Vec<Service>,
+    services_error: Option<String>,
+    /// Whether the last refresh asked for memory and uptime.
+    runtime_requested: Option<bool>,
+    logs: Vec<String>,
+    logs_error: Option<String>,
+    boot_failures: Vec<BootFailure>,
+    dependencies: HashMap<String, Vec<Relation>>,
+    documentation: HashMap<String, Vec<String>>,
+    /// Units that only exist for `get_unit`, like sockets and timers.
+    other_units: Vec<Service>,
+    /// Other names units answer to, like dbus.service for dbus-broker.service.
+    aliases: HashMap<String, String>,
+    actions: Vec<(String, ServiceAction)>,
+    unit_file_changes: Vec<(Vec<String>, UnitFileAction)>,
+    notifications: Vec<String>,
+    machines: Vec<Machine>,
+    machine_actions: Vec<(String, MachineAction)>,
+    opened_docs: Vec<String>,
+    /// How many times any backend method was called.
+    calls: usize,
+}
+
+/// A cheaply clonable handle, so a test can keep one copy while the app owns another.
+#[derive(Clone, Default)]
+pub struct MockClient {
+    state: Rc<RefCell<MockState>>,
+}
+
+impl MockClient {
+    pub fn new(services: Vec<Service>) -> Self {
+        let client = Self::default();
+        client.set_services(services);
+        client
+    }
+
+    /// Serves the units parsed from captured `list-units` / `list-unit-files` output.
+    pub fn from_fixtures(list_units: &str, list_unit_files: &str, user_config: &[&str]) -> Self {
+        let user_config: HashSet<String> = user_config.iter().map(|s| s.to_string()).collect();
+        Self::new(parse_services(list_units, list_unit_files, &user_config))
+    }
+
+    pub fn set_services(&self, services: Vec<Service>) {
+        self.state.borrow_mut().services = services;
+    }
+
+    /// Makes listing services fail with the given message until cleared.
+    pub fn set_services_error(&self, error: Option<&str>) {
+        self.state.borrow_mut().services_error = error.map(str::to_string);
+    }
+
+    pub fn set_logs(&self, logs: Vec<String>) {
+        self.state.borrow_mut().logs = logs;
+    }
+
+    /// Makes fetching logs fail as if the journal were unreadable.
+    pub fn set_logs_error(&self, error: Option<&str>) {
+        self.state.borrow_mut().logs_error = error.map(str::to_string);
+    }
+
+    pub fn set_boot_failures(&self, failures: Vec<BootFailure>) {
+        self.state.borrow_mut().boot_failures = failures;
+    }
+
+    /// Links `unit` to others, given as (property, unit) pairs.
+    pub fn set_dependencies(&self, unit: &str, relations: &[(&str, &str)]) {
+        let relations = relations
+            .iter()
+            .map(|(kind, other)| Relation {
+                kind: kind.to_string(),
+                unit: other.to_string(),
+            })
+            .collect();
+        self.state
+            .borrow_mut()
+            .dependencies
+            .insert(unit.to_string(), relations);
+    }
+
+    pub fn set_documentation(&self, unit: &str, uris: &[&str]) {
+        self.state.borrow_mut().documentation.insert(
+            unit.to_string(),
+            uris.iter().map(|uri| uri.to_string()).collect(),
+        );
+    }
+
+    pub fn set_other_units(&self, units: Vec<Service>) {
+        self.state.borrow_mut().other_units = units;
+    }
+
+    pub fn set_alias(&self, alias: &str, unit: &str) {
+        self.state
+            .borrow_mut()
+            .aliases
+            .insert(alias.to_string(), unit.to_string());
+    }
+
+    pub fn runtime_requested(&self) -> Option<bool> {
+        self.state.borrow().runtime_requested
+    }
+
+    /// Every action the app asked for, in order.
+    pub fn actions(&self) -> Vec<(String, ServiceAction)> {
+        self.state.borrow().actions.clone()
+    }
+
+    pub fn set_machines(&self, machines: Vec<Machine>) {
+        self.state.borrow_mut().machines = machines;
+    }
+
+    /// Every machine action the app asked for, in order.
+    pub fn machine_actions(&self) -> Vec<(String, MachineAction)> {
+        self.state.borrow().machine_actions.clone()
+    }
+
+    /// Every documentation URI the app asked to open, in order.
+    pub fn opened_docs(&self) -> Vec<String> {
+        self.state.borrow().opened_docs.clone()
+    }
+
+    pub fn calls(&self) -> usize {
+        self.state.borrow().calls
+    }
+
+    /// Borrows the state for a backend method, counting the call.
+    fn call(&self) -> RefMut<'_, MockState> {
+        let mut state = self.state.borrow_mut();
+        state.calls += 1;
+        state
+    }
+
+    /// The body of every notification sent, in order.
+    pub fn notifications(&self) -> Vec<String> {
+        self.state.borrow().notifications.clone()
+    }
+
+    /// Every enable/disable/mask the app carried out, in order.
+    pub fn unit_file_changes(&self) -> Vec<(Vec<String>, UnitFileAction)> {
+        self.state.borrow().unit_file_changes.clone()
+    }
+}
+
+impl SystemdClient for MockClient {
+    fn get_user_services(&self, runtime: bool) -> Result<Vec<Service>> {
+        let mut state = self.call();
+        state.runtime_requested = Some(runtime);
+        match &state.services_error {
+            Some(error) => Err(anyhow::anyhow!("{}", error)),
+            None => Ok(state.services.clone()),
+        }
+    }
+
+    fn control_service(&self, service_name: &str, action: ServiceAction) -> Result<()> {
+        self.call().actions.push((service_name.to_string(), action));
+        Ok(())
+    }
+
+    /// Pretends every unit is wanted by default.target, and that masking refuses unit files
+    /// defined in ~/.config/systemd/user.
+    fn preview_unit_files(
+        &self,
+        units: &[String],
+        action: UnitFileAction,
+    ) -> Result<Vec<SymlinkChange>> {
+        let state = self.call();
+        Ok(units
+            .iter()
+            .map(|unit| match action {
+                UnitFileAction::Enable => SymlinkChange::Create {
+                    link: PathBuf::from("default.target.wants").join(unit),
+                    target: PathBuf::from(unit),
+                },
+                UnitFileAction::Disable => SymlinkChange::Remove {
+                    link: PathBuf::from("default.target.wants").join(unit),
+                },
+                UnitFileAction::Mask => {
+                    let user_config = state
+                        .services
+                        .iter()
+                        .any(|s| &s.name == unit && s.is_user_config);
+                    if user_config {
+                        SymlinkChange::Blocked {
+                            link: PathBuf::from(unit),
+                            reason: "unit file exists".to_string(),
+                        }
+                    } else {
+                        SymlinkChange::Create {
+                            link: PathBuf::from(unit),
+                            target: PathBuf::from("/dev/null"),
+                        }
+                    }
+                }
+            })
+            .collect())
+    }
+
+    fn change_unit_files(&self, units: &[String], action: UnitFileAction) -> Result<()> {
+        self.call().unit_file_changes.push((units.to_vec(), action));
+        Ok(())
+    }
+
+    fn get_service_logs(&self, _service_name: &str, range: &LogRange) -> Result<LogChunk> {
+        let state = self.call();
+        match &state.logs_error {
+            Some(error) => Err(JournalUnavailable(error.clone()).into()),
+            None => Ok(log_chunk(&state.logs, range)),
+        }
+    }
+
+    fn get_boot_failures(&self) -> Result<Vec<BootFailure>> {
+        Ok(self.call().boot_failures.clone())
+    }
+
+    /// Treats the logs as this unit's entries out of a journal ten times larger.
+    fn get_journal_usage(&self, unit: &str) -> Result<JournalUsage> {
+        let state = self.call();
+        Ok(JournalUsage {
+            unit: unit.to_string(),
+            unit_entries: state.logs.len() as u64,
+            total_entries: state.logs.len() as u64 * 10,
+            disk_usage: Some("96.0M".to_string()),
+        })
+    }
+
+    fn get_machines(&self) -> Result<Vec<Machine>> {
+        Ok(self.call().machines.clone())
+    }
+
+    fn control_machine(&self, name: &str, action: MachineAction) -> Result<()> {
+        self.call().machine_actions.push((name.to_string(), action));
+        Ok(())
+    }
+
+    /// Machines share the unit logs, tagged with the machine name.
+    fn get_machine_logs(&self, name: &str, range: &LogRange) -> Result<LogChunk> {
+        let mut chunk = log_chunk(&self.call().logs, range);
+        for line in &mut chunk.lines {
+            *line = format!("{}: {}", name, line);
+        }
+        Ok(chunk)
+    }
+
+    fn get_dependencies(&self, unit: &str) -> Result<Vec<Relation>> {
+        Ok(self
+            .call()
+            .dependencies
+            .get(unit)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    fn get_documentation(&self, unit: &str) -> Result<Vec<String>> {
+        Ok(self
+            .call()
+            .documentation
+            .get(unit)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    /// Resolves aliases the way `systemctl show` does, returning the unit under its own name.
+    fn get_unit(&self, unit: &str) -> Result<Service> {
+        let state = self.call();
+        let unit = state.aliases.get(unit).map_or(unit, String::as_str);
+        state
+            .services
+            .iter()
+            .chain(&state.other_units)
+            .find(|s| s.name == unit)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Unit {} not found", unit))
+    }
+
+    /// Remembers the URI instead, so tests never start man or a browser.
+    fn should_open_documentation(&self, uri: &str) -> bool {
+        self.call().opened_docs.push(uri.to_string());
+        false
+    }
+
+    fn notify(&self, _summary: &str, body: &str) {
+        self.call().notifications.push(body.to_string());
+    }
+
+    fn watch_units(&self) -> Option<Receiver<()>> {
+        None
+    }
+}
+
+/// Each line is an entry whose cursor is its index, so lines added later read as new.
+fn log_chunk(logs: &[String], range: &LogRange) -> LogChunk {
+    let index = |cursor: &str| cursor.parse::<usize>().unwrap_or(0).min(logs.len());
+    let (start, end) = match range {
+        LogRange::Last(lines) => (logs.len().saturating_sub(*lines), logs.len()),
+        LogRange::After(cursor) => ((index(cursor) + 1).min(logs.len()), logs.len()),
+        LogRange::Before(cursor, entries) => {
+            let end = index(cursor);
+            (end.saturating_sub(*entries), end)
+        }
+    };
+    // Like journalctl, the cursor is left out when nothing was read.
+    let last = match range {
+        LogRange::Before(..) => start,
+        _ => end.saturating_sub(1),
+    };
+    LogChunk {
+        lines: logs[start..end].to_vec(),
+        cursor: (start < end).then(|| last.to_string()),
+    }
+}
+
+/// A loaded service in the given state.
+pub fn service(name: &str, active_state: &str, sub_state: &str, is_user_config: bool) -> Service {
+    Service {
+        name: name.to_string(),
+        active_state: active_state.to_string(),
+        sub_state: sub_state.to_string(),
+        loaded_state: "loaded".to_string(),
+        description: String::new(),
+        enablement: String::new(),
+        memory_bytes: None,
+        active_since: None,
+        is_user_config,
+    }
+}
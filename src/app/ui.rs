@@ -1,47 +1,177 @@
 // Handles the rendering of widgets to the terminal frame.
 
-use super::model::Service;
+use super::App;
+use super::config::Column;
+use super::diagnostics::Diagnostic;
+use super::format::{format_bytes, format_duration};
+use super::model::{BootFailure, JournalUsage, Machine, Relation, Service, SymlinkChange};
+use super::systemd::UnitFileAction;
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
 };
+use std::collections::BTreeSet;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Main render function called every frame.
+pub fn render(f: &mut Frame, app: &mut App) {
+    if let Some(diagnostic) = &app.diagnostic {
+        render_diagnostic(f, diagnostic);
+        return;
+    }
 
-pub fn render(
-    f: &mut Frame,
-    services: &[Service],
-    list_state: &mut ListState,
-    show_only_config: bool,
-    showing_logs: bool,
-    logs: &[String],
-    log_scroll: u16,
-    stick_to_bottom: bool,
-) {
+    let services = app.get_current_view_services();
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(5)])
+        .split(f.area());
+
+    let title = list_title(app);
+    render_service_list(
+        f,
+        chunks[0],
+        &services,
+        &app.columns,
+        &app.marked,
+        &mut app.list_state,
+        &title,
+    );
+    render_footer(f, chunks[1], app);
+
+    if app.showing_logs {
+        let owner = match &app.log_machine {
+            Some(machine) => format!("machine {}", machine),
+            None => app.log_unit.clone().unwrap_or_default(),
+        };
+        render_logs(f, &owner, &app.logs, app.log_scroll, app.stick_to_bottom);
+        if let Some(usage) = &app.journal_usage {
+            render_journal_usage(f, usage);
+        }
+    } else if app.showing_machines {
+        render_machines(f, &app.machines, app.machine_cursor);
+    } else if app.showing_failures {
+        render_failures(f, &app.boot_failures, app.failures_scroll);
+    } else if app.showing_columns {
+        render_column_chooser(f, &app.columns, app.column_cursor);
+    } else if app.showing_docs {
+        render_docs(f, &app.doc_links, app.doc_cursor);
+    } else if let Some(action) = app.pending_unit_files {
+        render_unit_file_summary(f, action, &app.pending_units, &app.pending_changes);
+    } else if app.showing_dependencies {
+        render_dependencies(
+            f,
+            &app.dependencies_of,
+            &app.dependencies,
+            app.dependency_cursor,
+        );
+    }
+}
+
+/// Full-screen explanation shown when systemd can't be reached.
+fn render_diagnostic(f: &mut Frame, diagnostic: &Diagnostic) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Min(0), Constraint::Length(3)])
         .split(f.area());
 
-    render_service_list(f, chunks[0], services, list_state, show_only_config);
-    render_footer(f, chunks[1], showing_logs);
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+
+    let mut lines = vec![
+        Line::from(diagnostic.cause.as_str()),
+        Line::from(""),
+        Line::from(Span::styled("Possible fixes:", bold)),
+    ];
+    lines.extend(
+        diagnostic
+            .fixes
+            .iter()
+            .map(|fix| Line::from(format!("  • {}", fix))),
+    );
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled("Details:", bold)));
+    lines.push(Line::from(Span::styled(
+        format!("  {}", diagnostic.detail),
+        Style::default().fg(Color::Gray),
+    )));
 
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Red))
+        .title(format!(" {} ", diagnostic.title));
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, chunks[0]);
+
+    let help_text = Line::from(vec![
+        Span::raw("Retry: "),
+        Span::styled("r ", bold),
+        Span::raw("| Quit: "),
+        Span::styled("q", Style::default().fg(Color::Red)),
+    ]);
+    let footer =
+        Paragraph::new(help_text).block(Block::default().borders(Borders::ALL).title(" Controls "));
+    f.render_widget(footer, chunks[1]);
+}
 
-    if showing_logs {
-        render_logs(f, logs, log_scroll, stick_to_bottom);
+/// Describes which scope and filters are shaping the list.
+fn list_title(app: &App) -> String {
+    let scope = if app.show_only_user_config {
+        "~/.config/systemd/user Services"
+    } else {
+        "All User Services"
+    };
+
+    let mut title = format!(" {} ", scope);
+    if let Some(i) = app.active_preset {
+        title.push_str(&format!("[{}] ", app.presets[i].name));
+    }
+    // While the search box holds an invalid regex the previous filter is still the one applied.
+    if let Some(filter) = &app.filter {
+        title.push_str(&format!("/{} ", filter.query()));
+    }
+    if app.hide_inactive {
+        title.push_str("(active only) ");
     }
+    if !app.marked.is_empty() {
+        // Marks outside the current view are still acted on, so say how many aren't on screen.
+        let visible = app.get_current_view_services();
+        let hidden = app
+            .marked
+            .iter()
+            .filter(|name| !visible.iter().any(|s| &&s.name == name))
+            .count();
+        if hidden > 0 {
+            title.push_str(&format!(
+                "({} marked, {} hidden) ",
+                app.marked.len(),
+                hidden
+            ));
+        } else {
+            title.push_str(&format!("({} marked) ", app.marked.len()));
+        }
+    }
+    title
 }
 
 fn render_service_list(
     f: &mut Frame,
     area: Rect,
     services: &[Service],
+    columns: &[Column],
+    marked: &BTreeSet<String>,
     state: &mut ListState,
-    show_only_config: bool,
+    title: &str,
 ) {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+
     let items: Vec<ListItem> = services
         .iter()
         .map(|service| {
@@ -55,28 +185,32 @@ fn render_service_list(
 
             let config_indicator = if service.is_user_config { "*" } else { " " };
 
-            let content = Line::from(vec![
+            let mut spans = vec![
                 Span::styled(
                     format!("{}{}", config_indicator, status_symbol),
                     Style::default().fg(color),
                 ),
-                Span::raw(format!(" {:<40}", service.name)),
+                Span::raw(format!(
+                    "{}{:<40}",
+                    if marked.contains(&service.name) {
+                        "✓"
+                    } else {
+                        " "
+                    },
+                    service.name
+                )),
+            ];
+            spans.extend(columns.iter().map(|&column| {
                 Span::styled(
-                    format!("[{}::{}]", service.loaded_state, service.sub_state),
+                    column_cell(service, column, now),
                     Style::default().fg(Color::Gray),
-                ),
-            ]);
+                )
+            }));
 
-            ListItem::new(content)
+            ListItem::new(Line::from(spans))
         })
         .collect();
 
-    let title = if show_only_config {
-        " ~/.config/systemd/user Services "
-    } else {
-        " All User Services "
-    };
-
     let list = List::new(items)
         .block(Block::default().borders(Borders::ALL).title(title))
         .highlight_style(
@@ -89,63 +223,547 @@ fn render_service_list(
     f.render_stateful_widget(list, area, state);
 }
 
+/// One fixed-width cell, so columns line up from row to row.
+fn column_cell(service: &Service, column: Column, now: u64) -> String {
+    match column {
+        Column::Load => format!("{:<10}", service.loaded_state),
+        Column::State => format!("{:<10}", service.active_state),
+        Column::SubState => format!("{:<10}", service.sub_state),
+        Column::Enablement => format!("{:<10}", service.enablement),
+        Column::Memory => format!(
+            "{:>7}   ",
+            service.memory_bytes.map(format_bytes).unwrap_or_default()
+        ),
+        Column::Uptime => format!(
+            "{:>7}   ",
+            service
+                .active_since
+                .map(|since| format_duration(now.saturating_sub(since)))
+                .unwrap_or_default()
+        ),
+        // Last, so it can take whatever width is left.
+        Column::Description => service.description.clone(),
+    }
+}
+
+fn render_footer(f: &mut Frame, area: Rect, app: &App) {
+    let filter_input = app.editing_filter.then_some(app.filter_query.as_str());
+
+    let logs_key = if app.journal_unavailable {
+        Span::styled("l(file only) ", Style::default().fg(Color::DarkGray))
+    } else {
+        Span::styled("l ", Style::default().add_modifier(Modifier::BOLD))
+    };
 
-fn render_footer(f: &mut Frame, area: Rect, showing_logs: bool) {
-    let help_text = if showing_logs {
-        Line::from(vec![
+    let help_text: Vec<Line> = if let Some(query) = filter_input {
+        vec![Line::from(vec![
+            Span::styled("/", Style::default().fg(Color::Cyan)),
+            Span::raw(query),
+            Span::styled("█ ", Style::default().fg(Color::Cyan)),
+            Span::raw("| Fuzzy, or "),
+            Span::styled("re:", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" for regex | Keep: "),
+            Span::styled("Enter ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw("| Clear: "),
+            Span::styled("Esc", Style::default().fg(Color::Red)),
+        ])]
+    } else if app.journal_usage.is_some() {
+        vec![Line::from(vec![
+            Span::raw("Close: "),
+            Span::styled("Esc/q/V ", Style::default().fg(Color::Red)),
+        ])]
+    } else if app.showing_logs {
+        let mut keys = vec![
             Span::raw("Scroll: "),
             Span::styled("j/k ", Style::default().add_modifier(Modifier::BOLD)),
-            Span::raw("| Auto-Scroll: "),
+            Span::raw("(k at top loads older) | Auto-Scroll: "),
             Span::styled("G ", Style::default().add_modifier(Modifier::BOLD)),
-            Span::raw("| Close: "),
-            Span::styled("Esc/q/l ", Style::default().fg(Color::Red)),
-        ])
-    } else {
-        Line::from(vec![
-            Span::raw("Nav: "),
+        ];
+        // Journal usage is per unit, so a machine's journal doesn't offer it.
+        if app.log_machine.is_none() {
+            keys.push(Span::raw("| Journal usage: "));
+            keys.push(Span::styled(
+                "V ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ));
+        }
+        keys.push(Span::raw("| Close: "));
+        keys.push(Span::styled("Esc/q/l ", Style::default().fg(Color::Red)));
+        vec![Line::from(keys)]
+    } else if app.showing_machines {
+        vec![Line::from(vec![
+            Span::raw("Move: "),
             Span::styled("j/k ", Style::default().add_modifier(Modifier::BOLD)),
-            Span::raw("| View: "),
-            Span::styled("Tab ", Style::default().add_modifier(Modifier::BOLD)),
-            Span::raw("| Logs: "),
-            Span::styled("l ", Style::default().add_modifier(Modifier::BOLD)),
             Span::raw("| Action: "),
             Span::styled(
-                "s(start) x(stop) r(restart) ",
+                "s(start) x(poweroff) t(terminate) ",
                 Style::default().fg(Color::Cyan),
             ),
-            Span::raw("| Quit: "),
-            Span::styled("q", Style::default().fg(Color::Red)),
-        ])
+            Span::raw("| Journal: "),
+            Span::styled("l ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw("| Close: "),
+            Span::styled("Esc/q/m ", Style::default().fg(Color::Red)),
+        ])]
+    } else if app.showing_failures {
+        vec![Line::from(vec![
+            Span::raw("Scroll: "),
+            Span::styled("j/k ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw("| Close: "),
+            Span::styled("Esc/q/b ", Style::default().fg(Color::Red)),
+        ])]
+    } else if app.pending_unit_files.is_some() && app.unit_file_action_blocked() {
+        vec![Line::from(vec![
+            Span::raw("Cancel: "),
+            Span::styled("Esc/n ", Style::default().fg(Color::Red)),
+        ])]
+    } else if app.pending_unit_files.is_some() {
+        vec![Line::from(vec![
+            Span::raw("Apply: "),
+            Span::styled("y/Enter ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw("| Cancel: "),
+            Span::styled("Esc/n ", Style::default().fg(Color::Red)),
+        ])]
+    } else if app.showing_dependencies {
+        vec![Line::from(vec![
+            Span::raw("Move: "),
+            Span::styled("j/k ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw("| Go to unit: "),
+            Span::styled("Enter ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw("| Close: "),
+            Span::styled("Esc/q/d ", Style::default().fg(Color::Red)),
+        ])]
+    } else if app.showing_docs {
+        vec![Line::from(vec![
+            Span::raw("Move: "),
+            Span::styled("j/k ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw("| Open: "),
+            Span::styled("Enter ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw("| Close: "),
+            Span::styled("Esc/q/o ", Style::default().fg(Color::Red)),
+        ])]
+    } else if app.showing_columns {
+        vec![Line::from(vec![
+            Span::raw("Move: "),
+            Span::styled("j/k ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw("| Toggle: "),
+            Span::styled("Space ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw("| Save & Close: "),
+            Span::styled("Esc/q/c ", Style::default().fg(Color::Red)),
+        ])]
+    } else {
+        vec![
+            Line::from(vec![
+                Span::raw("Nav: "),
+                Span::styled("j/k ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw("| View: "),
+                Span::styled("Tab ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw("| Search: "),
+                Span::styled("/ ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw("| Preset: "),
+                Span::styled("p ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw("| Hide inactive: "),
+                Span::styled("h ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw("| Columns: "),
+                Span::styled("c ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw("| Docs: "),
+                Span::styled("o ", Style::default().add_modifier(Modifier::BOLD)),
+            ]),
+            Line::from(vec![
+                Span::raw("Logs: "),
+                logs_key,
+                Span::raw("| Deps: "),
+                Span::styled("d ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw("| Failures: "),
+                Span::styled("b ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw("| Action: "),
+                Span::styled(
+                    "s(start) x(stop) r(restart) ",
+                    Style::default().fg(Color::Cyan),
+                ),
+                Span::raw("| Quit: "),
+                Span::styled("q", Style::default().fg(Color::Red)),
+            ]),
+            Line::from(vec![
+                Span::raw("Mark: "),
+                Span::styled("Space ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw("| Unmark all: "),
+                Span::styled("Esc ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw("| Unit files: "),
+                Span::styled(
+                    "E(enable) D(disable) M(mask) ",
+                    Style::default().fg(Color::Cyan),
+                ),
+                Span::raw("| Machines: "),
+                Span::styled("m ", Style::default().add_modifier(Modifier::BOLD)),
+            ]),
+        ]
     };
 
-    let paragraph =
-        Paragraph::new(help_text).block(Block::default().borders(Borders::ALL).title(" Controls "));
+    let mut block = Block::default().borders(Borders::ALL).title(" Controls ");
+    if let Some(message) = &app.status_message {
+        block = block.title_top(
+            Line::from(format!(" {} ", message))
+                .style(Style::default().fg(Color::Yellow))
+                .right_aligned(),
+        );
+    }
+
+    let paragraph = Paragraph::new(help_text).block(block);
 
     f.render_widget(paragraph, area);
 }
 
-
-fn render_logs(f: &mut Frame, logs: &[String], scroll: u16, stick_to_bottom: bool) {
+/// `owner` names whose journal this is: a unit, or "machine <name>".
+fn render_logs(f: &mut Frame, owner: &str, logs: &[String], scroll: usize, stick_to_bottom: bool) {
     let area = centered_rect(80, 80, f.area());
 
     f.render_widget(Clear, area);
 
-
     let title = if stick_to_bottom {
-        " Service Logs (Live | Auto-scroll: ON) - Press 'j/k' to pause "
+        format!(
+            " Logs: {} (Live | Auto-scroll: ON) - Press 'j/k' to pause ",
+            owner
+        )
     } else {
-        " Service Logs (Paused | Auto-scroll: OFF) - Press 'G' to resume "
+        format!(
+            " Logs: {} (Paused | Auto-scroll: OFF) - Press 'G' to resume ",
+            owner
+        )
     };
 
     let block = Block::default().borders(Borders::ALL).title(title);
 
-    let content: Vec<Line> = logs.iter().map(|s| Line::from(s.as_str())).collect();
+    // Only build lines for what fits, since the buffer can hold thousands.
+    let height = block.inner(area).height as usize;
+    let content: Vec<Line> = visible_slice(logs, scroll, height)
+        .iter()
+        .map(|s| Line::from(s.as_str()))
+        .collect();
+
+    let paragraph = Paragraph::new(content).block(block);
+
+    f.render_widget(paragraph, area);
+}
+
+/// The lines of a scrolled, unwrapped view that are actually on screen.
+fn visible_slice(lines: &[String], scroll: usize, height: usize) -> &[String] {
+    let start = scroll.min(lines.len());
+    let end = start.saturating_add(height).min(lines.len());
+    &lines[start..end]
+}
+
+fn render_journal_usage(f: &mut Frame, usage: &JournalUsage) {
+    let area = centered_rect(60, 40, f.area());
+
+    f.render_widget(Clear, area);
+
+    let title = format!(" Journal Usage: {} ", usage.unit);
+    let block = Block::default().borders(Borders::ALL).title(title);
+
+    let share = if usage.total_entries == 0 {
+        0.0
+    } else {
+        usage.unit_entries as f64 * 100.0 / usage.total_entries as f64
+    };
+
+    let content = vec![
+        Line::from(format!(
+            "{} of {} user journal entries this boot ({:.1}%)",
+            usage.unit_entries, usage.total_entries, share
+        )),
+        Line::from(format!(
+            "On disk: {}",
+            usage.disk_usage.as_deref().unwrap_or("unknown")
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            "User units share one journal, and journald only vacuums whole journals, \
+             so this unit's entries can't be trimmed on their own.",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+
+    let paragraph = Paragraph::new(content)
+        .block(block)
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(paragraph, area);
+}
+
+fn render_machines(f: &mut Frame, machines: &[Machine], cursor: usize) {
+    let area = centered_rect(80, 80, f.area());
+
+    f.render_widget(Clear, area);
+
+    let title = format!(" Machines ({}) ", machines.len());
+    let block = Block::default().borders(Borders::ALL).title(title);
+
+    if machines.is_empty() {
+        let paragraph =
+            Paragraph::new("No machines or images. Is systemd-machined running?").block(block);
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    let items: Vec<ListItem> = machines
+        .iter()
+        .map(|machine| {
+            let (symbol, color, state) = if machine.running {
+                ("●", Color::Green, "running")
+            } else {
+                ("○", Color::DarkGray, "stopped")
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{} ", symbol), Style::default().fg(color)),
+                Span::raw(format!("{:<30}", machine.name)),
+                Span::styled(
+                    format!("{:<10}{:<11}{}", state, machine.class, machine.os),
+                    Style::default().fg(Color::Gray),
+                ),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .bg(Color::DarkGray),
+        )
+        .highlight_symbol(">> ");
+
+    f.render_stateful_widget(
+        list,
+        area,
+        &mut ListState::default().with_selected(Some(cursor)),
+    );
+}
+
+fn render_failures(f: &mut Frame, failures: &[BootFailure], scroll: u16) {
+    let area = centered_rect(80, 80, f.area());
+
+    f.render_widget(Clear, area);
+
+    let title = format!(" Failures Since Boot ({} units) ", failures.len());
+    let block = Block::default().borders(Borders::ALL).title(title);
+
+    let content: Vec<Line> = if failures.is_empty() {
+        vec![Line::from("No units have failed since boot.")]
+    } else {
+        failures
+            .iter()
+            .flat_map(|failure| {
+                let times = if failure.count > 1 {
+                    format!(" (x{})", failure.count)
+                } else {
+                    String::new()
+                };
+                let last_line = if failure.last_line.is_empty() {
+                    "(no output)"
+                } else {
+                    failure.last_line.as_str()
+                };
+
+                [
+                    Line::from(vec![
+                        Span::styled(
+                            format!("{} ", failure.time),
+                            Style::default().fg(Color::Gray),
+                        ),
+                        Span::styled(
+                            format!("✖ {} ", failure.unit),
+                            Style::default().fg(Color::Red),
+                        ),
+                        Span::raw(format!("[{}]{}", failure.result, times)),
+                    ]),
+                    Line::from(Span::styled(
+                        format!("    {}", last_line),
+                        Style::default().fg(Color::DarkGray),
+                    )),
+                ]
+            })
+            .collect()
+    };
 
     let paragraph = Paragraph::new(content).block(block).scroll((scroll, 0));
 
     f.render_widget(paragraph, area);
 }
 
+fn render_unit_file_summary(
+    f: &mut Frame,
+    action: UnitFileAction,
+    units: &[String],
+    changes: &[SymlinkChange],
+) {
+    let area = centered_rect(80, 80, f.area());
+
+    f.render_widget(Clear, area);
+
+    let title = format!(
+        " {} {} unit{}? ",
+        action.label(),
+        units.len(),
+        if units.len() == 1 { "" } else { "s" }
+    );
+    let block = Block::default().borders(Borders::ALL).title(title);
+
+    let mut content = vec![
+        Line::from(Span::styled(
+            units.join(", "),
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
+    if changes.is_empty() {
+        content.push(Line::from(Span::styled(
+            "No symlinks will change.",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    content.extend(changes.iter().map(|change| match change {
+        SymlinkChange::Create { link, target } => Line::from(Span::styled(
+            format!("+ {} → {}", link.display(), target.display()),
+            Style::default().fg(Color::Green),
+        )),
+        SymlinkChange::Remove { link } => Line::from(Span::styled(
+            format!("- {}", link.display()),
+            Style::default().fg(Color::Red),
+        )),
+        SymlinkChange::Blocked { link, reason } => Line::from(Span::styled(
+            format!("✖ {}: {}", link.display(), reason),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        )),
+    }));
+    if changes
+        .iter()
+        .any(|change| matches!(change, SymlinkChange::Blocked { .. }))
+    {
+        content.push(Line::from(""));
+        content.push(Line::from(Span::styled(
+            format!("Can't {} while a unit above is refused.", action.verb()),
+            Style::default().fg(Color::Red),
+        )));
+    }
+
+    let paragraph = Paragraph::new(content)
+        .block(block)
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(paragraph, area);
+}
+
+fn render_dependencies(f: &mut Frame, unit: &str, relations: &[Relation], cursor: usize) {
+    let area = centered_rect(80, 80, f.area());
+
+    f.render_widget(Clear, area);
+
+    let title = format!(" Dependencies of {} ", unit);
+    let block = Block::default().borders(Borders::ALL).title(title);
+
+    if relations.is_empty() {
+        let paragraph = Paragraph::new("No dependencies.").block(block);
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    let items: Vec<ListItem> = relations
+        .iter()
+        .map(|relation| {
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{:<12}", relation.kind),
+                    Style::default().fg(Color::Gray),
+                ),
+                Span::raw(relation.unit.as_str()),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .bg(Color::DarkGray),
+        )
+        .highlight_symbol(">> ");
+
+    f.render_stateful_widget(
+        list,
+        area,
+        &mut ListState::default().with_selected(Some(cursor)),
+    );
+}
+
+fn render_docs(f: &mut Frame, links: &[String], cursor: usize) {
+    let area = centered_rect(60, 40, f.area());
+
+    f.render_widget(Clear, area);
+
+    let items: Vec<ListItem> = links
+        .iter()
+        .map(|link| ListItem::new(link.as_str()))
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Documentation "),
+        )
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .bg(Color::DarkGray),
+        )
+        .highlight_symbol(">> ");
+
+    f.render_stateful_widget(
+        list,
+        area,
+        &mut ListState::default().with_selected(Some(cursor)),
+    );
+}
+
+fn render_column_chooser(f: &mut Frame, columns: &[Column], cursor: usize) {
+    // Fixed size: one line per column plus the border.
+    let area = centered_rect(30, 40, f.area());
+    let area = Rect {
+        height: area.height.min(Column::ALL.len() as u16 + 2),
+        ..area
+    };
+
+    f.render_widget(Clear, area);
+
+    let items: Vec<ListItem> = Column::ALL
+        .iter()
+        .map(|column| {
+            let mark = if columns.contains(column) {
+                "[x]"
+            } else {
+                "[ ]"
+            };
+            ListItem::new(format!("{} {}", mark, column.label()))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(" Columns "))
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .bg(Color::DarkGray),
+        );
+
+    f.render_stateful_widget(
+        list,
+        area,
+        &mut ListState::default().with_selected(Some(cursor)),
+    );
+}
+
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
@@ -164,4 +782,342 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
             Constraint::Percentage((100 - percent_x) / 2),
         ])
         .split(popup_layout[1])[1]
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::config::{Column, Config, Preset, Scope, SortOrder};
+    use crate::app::systemd::mock::{MockClient, service};
+    use insta::assert_snapshot;
+    use ratatui::{Terminal, backend::TestBackend};
+
+    fn app_with(services: Vec<Service>) -> App {
+        let mut app = App::new(Box::new(MockClient::new(services)), Config::default());
+        app.refresh_services().unwrap();
+        app
+    }
+
+    fn draw(width: u16, height: u16, app: &mut App) -> Terminal<TestBackend> {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(|f| render(f, app)).unwrap();
+        terminal
+    }
+
+    fn mixed_services() -> Vec<Service> {
+        vec![
+            service("backup.service", "inactive", "dead", true),
+            service("mpd.service", "active", "running", true),
+            service("syncthing.service", "failed", "failed", false),
+        ]
+    }
+
+    #[test]
+    fn empty_list() {
+        let terminal = draw(100, 10, &mut app_with(Vec::new()));
+        assert_snapshot!(terminal.backend());
+    }
+
+    #[test]
+    fn failed_units() {
+        let mut app = app_with(mixed_services());
+        app.show_only_user_config = false;
+        app.list_state.select(Some(2));
+
+        let terminal = draw(100, 10, &mut app);
+        assert_snapshot!(terminal.backend());
+    }
+
+    #[test]
+    fn log_popup_open() {
+        let mut app = app_with(mixed_services());
+        app.show_only_user_config = false;
+        app.showing_logs = true;
+        app.log_unit = Some("backup.service".to_string());
+        app.logs = (1..=20).map(|i| format!("line {i}")).collect();
+        app.log_scroll = 5;
+        app.stick_to_bottom = false;
+
+        let terminal = draw(100, 20, &mut app);
+        assert_snapshot!(terminal.backend());
+    }
+
+    #[test]
+    fn narrow_terminal() {
+        let mut app = app_with(mixed_services());
+        app.show_only_user_config = false;
+
+        let terminal = draw(40, 8, &mut app);
+        assert_snapshot!(terminal.backend());
+    }
+
+    #[test]
+    fn journal_unavailable_with_status() {
+        let mut app = app_with(mixed_services());
+        app.journal_unavailable = true;
+        app.status_message = Some("Logs unavailable: journalctl not found".to_string());
+
+        let terminal = draw(100, 8, &mut app);
+        assert_snapshot!(terminal.backend());
+    }
+
+    #[test]
+    fn preset_in_title() {
+        let mut app = app_with(mixed_services());
+        app.presets = vec![Preset {
+            name: "my-daemons".to_string(),
+            filter: Some("^(mpd|syncthing)".to_string()),
+            sort: SortOrder::State,
+            scope: Scope::All,
+        }];
+        app.cycle_preset();
+
+        let terminal = draw(120, 8, &mut app);
+        assert_snapshot!(terminal.backend());
+    }
+
+    #[test]
+    fn editing_regex_filter() {
+        let mut app = app_with(mixed_services());
+        app.show_only_user_config = false;
+        app.editing_filter = true;
+        app.filter_query = r"re:^(mpd|sync)".to_string();
+        app.update_filter();
+
+        let terminal = draw(100, 8, &mut app);
+        assert_snapshot!(terminal.backend());
+    }
+
+    #[test]
+    fn invalid_regex_keeps_previous_filter() {
+        let mut app = app_with(mixed_services());
+        app.show_only_user_config = false;
+        app.editing_filter = true;
+        app.filter_query = r"re:^mpd".to_string();
+        app.update_filter();
+        app.filter_query.push('(');
+        app.update_filter();
+
+        let terminal = draw(100, 8, &mut app);
+        assert_snapshot!(terminal.backend());
+    }
+
+    #[test]
+    fn hiding_inactive_units() {
+        let mut app = app_with(mixed_services());
+        app.show_only_user_config = false;
+        app.hide_inactive = true;
+
+        let terminal = draw(100, 9, &mut app);
+        assert_snapshot!(terminal.backend());
+    }
+
+    #[test]
+    fn boot_failures_popup() {
+        let mut app = app_with(mixed_services());
+        app.showing_failures = true;
+        app.boot_failures = vec![
+            BootFailure {
+                unit: "syncthing.service".to_string(),
+                time: "2026-10-17T04:11:09+0200".to_string(),
+                result: "timeout".to_string(),
+                count: 1,
+                last_line: String::new(),
+            },
+            BootFailure {
+                unit: "backup.service".to_string(),
+                time: "2026-10-17T03:04:12+0200".to_string(),
+                result: "exit-code".to_string(),
+                count: 2,
+                last_line: "rsync: connection unexpectedly closed".to_string(),
+            },
+        ];
+
+        let terminal = draw(100, 14, &mut app);
+        assert_snapshot!(terminal.backend());
+    }
+
+    #[test]
+    fn extra_columns() {
+        let mut services = mixed_services();
+        services[1].description = "Music Player Daemon".to_string();
+        services[1].enablement = "enabled".to_string();
+        services[1].memory_bytes = Some(25 * 1024 * 1024);
+        services[2].enablement = "disabled".to_string();
+
+        let mut app = app_with(services);
+        app.show_only_user_config = false;
+        app.columns = vec![
+            Column::State,
+            Column::Enablement,
+            Column::Memory,
+            Column::Description,
+        ];
+
+        let terminal = draw(120, 9, &mut app);
+        assert_snapshot!(terminal.backend());
+    }
+
+    #[test]
+    fn column_chooser_popup() {
+        let mut app = app_with(mixed_services());
+        app.showing_columns = true;
+        app.column_cursor = 3;
+
+        let terminal = draw(100, 20, &mut app);
+        assert_snapshot!(terminal.backend());
+    }
+
+    #[test]
+    fn log_slice_stays_in_bounds() {
+        let lines: Vec<String> = (1..=10).map(|i| format!("line {i}")).collect();
+
+        assert_eq!(visible_slice(&lines, 2, 3), ["line 3", "line 4", "line 5"]);
+        assert_eq!(visible_slice(&lines, 8, 5), ["line 9", "line 10"]);
+        assert!(visible_slice(&lines, 20, 5).is_empty());
+        assert!(visible_slice(&[], 0, 5).is_empty());
+    }
+
+    #[test]
+    fn dependencies_popup() {
+        let mut app = app_with(mixed_services());
+        app.showing_dependencies = true;
+        app.dependencies_of = "syncthing.service".to_string();
+        app.dependencies = [
+            ("Requires", "syncthing.socket"),
+            ("After", "basic.target"),
+            ("TriggeredBy", "syncthing.socket"),
+        ]
+        .iter()
+        .map(|(kind, unit)| Relation {
+            kind: kind.to_string(),
+            unit: unit.to_string(),
+        })
+        .collect();
+        app.dependency_cursor = 1;
+
+        let terminal = draw(100, 14, &mut app);
+        assert_snapshot!(terminal.backend());
+    }
+
+    #[test]
+    fn marked_units_and_enable_summary() {
+        let mut app = app_with(mixed_services());
+        app.show_only_user_config = false;
+        app.marked = ["backup.service", "syncthing.service"]
+            .map(String::from)
+            .into();
+        app.list_state.select(Some(1));
+
+        let terminal = draw(100, 10, &mut app);
+        assert_snapshot!("marked_units", terminal.backend());
+
+        app.begin_unit_file_action(UnitFileAction::Enable, &app.get_current_view_services());
+        let terminal = draw(100, 16, &mut app);
+        assert_snapshot!("enable_summary", terminal.backend());
+
+        // Backup is a ~/.config/systemd/user unit, so masking it is refused.
+        app.begin_unit_file_action(UnitFileAction::Mask, &app.get_current_view_services());
+        let terminal = draw(100, 16, &mut app);
+        assert_snapshot!("mask_blocked_summary", terminal.backend());
+    }
+
+    #[test]
+    fn title_counts_marks_hidden_by_the_filter() {
+        let mut app = app_with(mixed_services());
+        app.show_only_user_config = false;
+        app.marked = ["backup.service", "mpd.service"].map(String::from).into();
+        app.filter_query = "backup".to_string();
+        app.update_filter();
+
+        assert_eq!(
+            list_title(&app),
+            " All User Services /backup (2 marked, 1 hidden) "
+        );
+    }
+
+    #[test]
+    fn journal_usage_popup() {
+        let mut app = app_with(mixed_services());
+        app.showing_logs = true;
+        app.log_unit = Some("backup.service".to_string());
+        app.logs = vec!["started".to_string()];
+        app.journal_usage = Some(JournalUsage {
+            unit: "backup.service".to_string(),
+            unit_entries: 1250,
+            total_entries: 5000,
+            disk_usage: Some("96.0M".to_string()),
+        });
+
+        let terminal = draw(100, 20, &mut app);
+        assert_snapshot!(terminal.backend());
+    }
+
+    #[test]
+    fn documentation_links_popup() {
+        let mut app = app_with(mixed_services());
+        app.showing_docs = true;
+        app.doc_links = vec![
+            "man:mpd(1)".to_string(),
+            "https://www.musicpd.org/".to_string(),
+        ];
+        app.doc_cursor = 1;
+
+        let terminal = draw(100, 16, &mut app);
+        assert_snapshot!(terminal.backend());
+    }
+
+    #[test]
+    fn machines_popup() {
+        let mut app = app_with(mixed_services());
+        app.showing_machines = true;
+        app.machines = vec![
+            Machine {
+                name: "archlinux".to_string(),
+                class: String::new(),
+                os: String::new(),
+                running: false,
+            },
+            Machine {
+                name: "fedora-dev".to_string(),
+                class: "container".to_string(),
+                os: "fedora".to_string(),
+                running: true,
+            },
+        ];
+        app.machine_cursor = 1;
+
+        let terminal = draw(100, 16, &mut app);
+        assert_snapshot!(terminal.backend());
+    }
+
+    #[test]
+    fn machine_logs_popup() {
+        let mut app = app_with(mixed_services());
+        app.showing_logs = true;
+        app.log_machine = Some("fedora-dev".to_string());
+        app.logs = vec!["fedora-dev: Started Journal Service.".to_string()];
+
+        let terminal = draw(100, 16, &mut app);
+        assert_snapshot!(terminal.backend());
+    }
+
+    #[test]
+    fn diagnostic_screen() {
+        let diagnostic = Diagnostic {
+            title: "No user session bus".to_string(),
+            cause: "Neither XDG_RUNTIME_DIR nor DBUS_SESSION_BUS_ADDRESS is set.".to_string(),
+            fixes: vec![
+                "export XDG_RUNTIME_DIR=/run/user/$(id -u) and try again.".to_string(),
+                "Use `machinectl shell $USER@` instead of su/sudo.".to_string(),
+            ],
+            detail: "Failed to connect to bus: No medium found".to_string(),
+        };
+
+        let mut app = app_with(Vec::new());
+        app.diagnostic = Some(diagnostic);
+
+        let terminal = draw(80, 14, &mut app);
+        assert_snapshot!(terminal.backend());
+    }
+}
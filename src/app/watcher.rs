@@ -0,0 +1,86 @@
+// Listens for systemd D-Bus signals so unit changes show up without waiting for a poll.
+
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use zbus::blocking::{Connection, MessageIterator};
+use zbus::message::Type;
+use zbus::zvariant::OwnedValue;
+use zbus::{MatchRule, Message};
+
+const SYSTEMD_DESTINATION: &str = "org.freedesktop.systemd1";
+const SYSTEMD_PATH: &str = "/org/freedesktop/systemd1";
+const MANAGER_INTERFACE: &str = "org.freedesktop.systemd1.Manager";
+
+// Any of these means the output of `systemctl list-units` / `list-unit-files` may have changed.
+const UNIT_SIGNALS: [&str; 4] = ["UnitNew", "UnitRemoved", "JobRemoved", "UnitFilesChanged"];
+
+// PropertiesChanged fires constantly (CPU and memory accounting, timers), so it only
+// counts when one of these changed, e.g. a unit crashing without a job.
+const STATE_PROPERTIES: [&str; 2] = ["ActiveState", "SubState"];
+
+/// Subscribes to the user manager's unit signals and returns a channel that
+/// receives a message every time one arrives.
+///
+/// Returns `None` if the user bus is unreachable, in which case the caller
+/// should keep polling.
+pub fn spawn_unit_watcher() -> Option<Receiver<()>> {
+    let connection = Connection::session().ok()?;
+
+    // systemd only emits unit signals once at least one client has subscribed.
+    connection
+        .call_method(
+            Some(SYSTEMD_DESTINATION),
+            SYSTEMD_PATH,
+            Some(MANAGER_INTERFACE),
+            "Subscribe",
+            &(),
+        )
+        .ok()?;
+
+    let rule = MatchRule::builder()
+        .msg_type(Type::Signal)
+        .sender(SYSTEMD_DESTINATION)
+        .ok()?
+        .path_namespace(SYSTEMD_PATH)
+        .ok()?
+        .build();
+    let messages = MessageIterator::for_match_rule(rule, &connection, Some(256)).ok()?;
+
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        // The subscription lives as long as the connection, so keep it in the thread.
+        let _connection = connection;
+
+        for message in messages.flatten() {
+            let member = message.header().member().map(|member| member.to_string());
+            let is_unit_signal = match member.as_deref() {
+                Some("PropertiesChanged") => is_state_change(&message),
+                Some(member) => UNIT_SIGNALS.contains(&member),
+                None => false,
+            };
+
+            if is_unit_signal && tx.send(()).is_err() {
+                // The app has gone away.
+                break;
+            }
+        }
+    });
+
+    Some(rx)
+}
+
+/// Whether a PropertiesChanged signal touches a unit's active or sub state.
+fn is_state_change(message: &Message) -> bool {
+    let body = message.body();
+    let Ok((_, changed, invalidated)) =
+        body.deserialize::<(String, HashMap<String, OwnedValue>, Vec<String>)>()
+    else {
+        return false;
+    };
+
+    STATE_PROPERTIES.iter().any(|property| {
+        changed.contains_key(*property) || invalidated.iter().any(|p| p == property)
+    })
+}
@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{Context, Result, bail};
 use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture},
     execute,
@@ -6,10 +6,62 @@ use crossterm::{
 };
 use ratatui::{Terminal, backend::CrosstermBackend};
 use std::io;
+use std::path::PathBuf;
+
+use app::config::{self, Config};
+use app::recording::{Recorder, RecordingClient, Replay};
+use app::systemd::{SystemctlClient, SystemdClient};
 
 mod app;
 
+const USAGE: &str = "Usage: systemd-tui-monitor [--record <file> | --replay <file>]";
+
+enum Mode {
+    Live,
+    Record(PathBuf),
+    Replay(PathBuf),
+}
+
+fn parse_args() -> Result<Mode> {
+    let mut args = std::env::args().skip(1);
+    let mode = match args.next().as_deref() {
+        None => Mode::Live,
+        Some("--record") => Mode::Record(args.next().context(USAGE)?.into()),
+        Some("--replay") => Mode::Replay(args.next().context(USAGE)?.into()),
+        Some(other) => bail!("Unknown argument {}\n{}", other, USAGE),
+    };
+    if let Some(extra) = args.next() {
+        bail!("Unexpected argument {}\n{}", extra, USAGE);
+    }
+    Ok(mode)
+}
+
 fn main() -> Result<()> {
+    let mode = parse_args()?;
+
+    // Load config (or the recording) first so any errors print to a normal terminal.
+    let mut recorder = None;
+    let mut replay = None;
+    let (client, config): (Box<dyn SystemdClient>, Config) = match mode {
+        Mode::Live => (Box::new(SystemctlClient), config::load()?),
+        Mode::Record(path) => {
+            let config = config::load()?;
+            let rec = Recorder::create(&path, &config::source())?;
+            recorder = Some(rec.clone());
+            (
+                Box::new(RecordingClient::new(Box::new(SystemctlClient), rec)),
+                config,
+            )
+        }
+        Mode::Replay(path) => {
+            let loaded = Replay::load(&path)?;
+            let config = loaded.config()?;
+            let client = Box::new(loaded.client());
+            replay = Some(loaded);
+            (client, config)
+        }
+    };
+
     // 1. Setup Terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -18,9 +70,16 @@ fn main() -> Result<()> {
     let mut terminal = Terminal::new(backend)?;
 
     // 2. Create App instance
-    let mut app = app::App::new();
+    let mut app = app::App::new(client, config);
+    if let Some(recorder) = recorder {
+        app.set_recorder(recorder);
+    }
 
-    let res = app.run(&mut terminal);
+    // 3. Run, or play the recording back
+    let res = match &replay {
+        Some(replay) => app.replay(&mut terminal, replay),
+        None => app.run(&mut terminal),
+    };
 
     // 4. Teardown Terminal
     disable_raw_mode()?;
@@ -38,4 +97,3 @@ fn main() -> Result<()> {
 
     Ok(())
 }
-